@@ -4,14 +4,18 @@ extern crate proc_macro_crate;
 extern crate quote;
 extern crate syn;
 
+mod packets;
 mod parse;
+mod schema;
 
-use parse::{Data, Fields, TestGeneratorInput, TransmittableInput};
+use parse::{Condition, Data, Encoding, Field, FieldAttrs, Fields, TestGeneratorInput, TransmittableInput, Variant};
+use schema::impl_schema;
 use proc_macro::TokenStream;
-use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use proc_macro2::{Group, Ident, Span, TokenStream as TokenStream2, TokenTree};
 use proc_macro_crate::{crate_name, FoundCrate};
-use quote::quote;
-use syn::{parse_macro_input, Error, Result};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Error, GenericArgument, PathArguments, Result, Type};
 
 fn get_crate_name() -> Ident {
     let ident = match crate_name("transmittable").expect("transmittable is present in `Cargo.toml`") {
@@ -22,6 +26,373 @@ fn get_crate_name() -> Ident {
     Ident::new(ident.as_str(), Span::call_site())
 }
 
+// The wrapper type a field with a custom encoding is read as, whose `serialize_inner` and `encoded_len_inner`
+// functions write the field without wrapping it
+pub(crate) fn wrapper_type(crate_name: &Ident, field: &Field) -> Option<TokenStream2> {
+    let ty = &field.ty;
+
+    match field.attrs.encoding.as_ref()? {
+        Encoding::Serde | Encoding::Count(_) | Encoding::SizeBytes(_) | Encoding::Versioned => None,
+        Encoding::Len(len) => Some(quote!( #crate_name::Prefixed<#len, #ty> )),
+        Encoding::NulTerminated(Some(max)) => Some(quote!( #crate_name::NulTerminated<#ty, #max> )),
+        Encoding::NulTerminated(None) => Some(quote!( #crate_name::NulTerminated<#ty> )),
+        Encoding::Fixed { size, pad: Some(pad) } => Some(quote!( #crate_name::FixedStr<#size, #pad> )),
+        Encoding::Fixed { size, pad: None } => Some(quote!( #crate_name::FixedStr<#size> )),
+        Encoding::Rest => Some(quote!( #crate_name::Remaining<#ty> )),
+    }
+}
+
+// Fields going through serde or which are versioned themselves can only be decoded synchronously
+fn is_sync_only(field: &Field) -> bool {
+    matches!(field.attrs.encoding, Some(Encoding::Serde | Encoding::Versioned))
+}
+
+// Neither these nor fields whose length is stored in another field have a fixed size
+fn has_variable_size(field: &Field) -> bool {
+    is_sync_only(field) || matches!(field.attrs.encoding, Some(Encoding::Count(_) | Encoding::SizeBytes(_)))
+}
+
+// The version a nested versioned type is encoded with, which can't be newer than its own newest one
+fn nested_version(crate_name: &Ident, ty: &Type) -> TokenStream2 {
+    quote!( ::core::cmp::min(version, <#ty as #crate_name::Versioned>::VERSION) )
+}
+
+// The `T` of an `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let last = path.path.segments.last().filter(|last| last.ident == "Option")?;
+    let PathArguments::AngleBracketed(arguments) = &last.arguments else {
+        return None;
+    };
+
+    match arguments.args.first()? {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
+// Splits a conditional `Option<T>` field into its condition and the `T` written when it holds, which keeps the
+// encoding of the field
+pub(crate) fn conditional(field: &Field) -> Option<(&Condition, Field)> {
+    let condition = field.attrs.condition.as_ref()?;
+    let inner = Field {
+        ident: field.ident.clone(),
+        ty: option_inner(&field.ty)?.clone(),
+        attrs: FieldAttrs { since: None, condition: None, ..field.attrs.clone() },
+    };
+
+    Some((condition, inner))
+}
+
+// Replaces every `self.field` of a condition with the expression `field` gives for it
+fn replace_fields(tokens: TokenStream2, field: &mut impl FnMut(&Ident) -> TokenStream2) -> TokenStream2 {
+    let tokens = tokens.into_iter().collect::<Vec<_>>();
+    let mut replaced = TokenStream2::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match (&tokens[i], tokens.get(i + 1), tokens.get(i + 2)) {
+            (TokenTree::Ident(this), Some(TokenTree::Punct(dot)), Some(TokenTree::Ident(name))) if this == "self" && dot.as_char() == '.' => {
+                replaced.extend(field(name));
+                i += 3;
+                continue;
+            },
+            (TokenTree::Group(group), _, _) => {
+                let mut inner = Group::new(group.delimiter(), replace_fields(group.stream(), field));
+                inner.set_span(group.span());
+                replaced.extend([TokenTree::Group(inner)]);
+            },
+            (token, _, _) => replaced.extend([token.clone()]),
+        }
+
+        i += 1;
+    }
+
+    replaced
+}
+
+// Computes the `FIXED_SIZE` of a sequence of fields at compile time, conditional fields never have a fixed size
+fn fixed_size(crate_name: &Ident, fields: &Fields) -> TokenStream2 {
+    let sizes = fields.iter().map(|field| match (has_variable_size(field) || field.attrs.condition.is_some(), wrapper_type(crate_name, field)) {
+        (true, _) => quote!( None ),
+        (false, Some(wrapper)) => quote!( <#wrapper as #crate_name::Transmittable>::FIXED_SIZE ),
+        (false, None) => {
+            let ty = &field.ty;
+            quote!( <#ty as #crate_name::Transmittable>::FIXED_SIZE )
+        },
+    });
+
+    quote!( #crate_name::size::sum(&[#( #sizes ),*]) )
+}
+
+// The statement writing a single field, `value` being a reference to it and `sibling` giving a reference to another
+// field of the same struct or variant. Conditional fields fail to encode if their presence disagrees with the condition
+fn write_field(crate_name: &Ident, field: &Field, value: impl ToTokens, sibling: &dyn Fn(&Ident) -> TokenStream2) -> TokenStream2 {
+    if let Some((condition, inner)) = conditional(field) {
+        let condition = replace_fields(condition.expr.to_token_stream(), &mut |name| {
+            let sibling = sibling(name);
+            quote!( (*#sibling) )
+        });
+        let write = write_field(crate_name, &inner, quote!(present), sibling);
+
+        return quote! {
+            match (#condition, #value) {
+                (true, ::core::option::Option::Some(present)) => { #write },
+                (false, ::core::option::Option::None) => {},
+                (_, value) => return Err(#crate_name::Error::ConditionMismatch { present: value.is_some() }),
+            }
+        };
+    }
+
+    match &field.attrs.encoding {
+        Some(Encoding::Serde) => quote!( #crate_name::serde::serialize(#value, writer)?; ),
+        Some(Encoding::Count(name)) => {
+            let count = sibling(name);
+            quote! {
+                #crate_name::prefixed::check_count(#count, #value)?;
+                #crate_name::prefixed::Prefixable::serialize_contents(#value, writer)?;
+            }
+        },
+        Some(Encoding::SizeBytes(name)) => {
+            let size = sibling(name);
+            quote! {
+                #crate_name::prefixed::check_size(#size, #value)?;
+                #crate_name::prefixed::Prefixable::serialize_contents(#value, writer)?;
+            }
+        },
+        Some(Encoding::Versioned) => {
+            let version = nested_version(crate_name, &field.ty);
+            quote!( #crate_name::Versioned::serialize_versioned(#value, #version, writer)?; )
+        },
+        _ => match wrapper_type(crate_name, field) {
+            Some(wrapper) => quote!( <#wrapper>::serialize_inner(#value, writer)?; ),
+            None => quote!( #crate_name::Transmittable::serialize(#value, writer)?; ),
+        },
+    }
+}
+
+// The encoded length of a single field, `value` being a reference to it
+fn field_len(crate_name: &Ident, field: &Field, value: impl ToTokens) -> TokenStream2 {
+    if let Some((_, inner)) = conditional(field) {
+        let len = field_len(crate_name, &inner, quote!(present));
+        return quote!( match #value { ::core::option::Option::Some(present) => #len, ::core::option::Option::None => 0 } );
+    }
+
+    match &field.attrs.encoding {
+        Some(Encoding::Serde) => quote!( #crate_name::serde::encoded_len(#value) ),
+        Some(Encoding::Count(_) | Encoding::SizeBytes(_)) => quote!( #crate_name::prefixed::Prefixable::contents_len(#value) ),
+        Some(Encoding::Versioned) => {
+            let version = nested_version(crate_name, &field.ty);
+            quote!( #crate_name::Versioned::encoded_len_versioned(#value, #version) )
+        },
+        _ => match wrapper_type(crate_name, field) {
+            Some(wrapper) => quote!( <#wrapper>::encoded_len_inner(#value) ),
+            None => quote!( #crate_name::Transmittable::encoded_len(#value) ),
+        },
+    }
+}
+
+// The variable a field is read into before constructing the value
+fn field_variable(name: impl std::fmt::Display) -> Ident {
+    Ident::new(&format!("field_{}", name), Span::call_site())
+}
+
+// The expression reading a single value of type `ty` from `reader`, either synchronously or asynchronously
+// (the type has to be spelled out, as the future returned by `deserialize_async` can't be inferred)
+fn read_value(crate_name: &Ident, ty: impl ToTokens, asynchronous: bool) -> TokenStream2 {
+    if asynchronous {
+        quote!( <#ty as #crate_name::AsyncTransmittable>::deserialize_async(reader).await? )
+    } else {
+        quote!( <#ty as #crate_name::Transmittable>::deserialize(reader)? )
+    }
+}
+
+// Reads a single field, falling back to its default if it was added in a version newer than `version`. Conditions
+// are evaluated over the variables earlier fields were read into
+fn read_field(crate_name: &Ident, field: &Field, asynchronous: bool) -> TokenStream2 {
+    let read = match conditional(field) {
+        Some((condition, inner)) => {
+            let condition = replace_fields(condition.expr.to_token_stream(), &mut |name| field_variable(name).into_token_stream());
+            let read = read_field(crate_name, &inner, asynchronous);
+            quote!( if #condition { ::core::option::Option::Some(#read) } else { ::core::option::Option::None } )
+        },
+        None => read_encoded(crate_name, field, asynchronous),
+    };
+
+    match field.attrs.since {
+        Some(since) => quote!( if version >= #since { #read } else { ::core::default::Default::default() } ),
+        None => read,
+    }
+}
+
+// Reads a single field with its encoding
+fn read_encoded(crate_name: &Ident, field: &Field, asynchronous: bool) -> TokenStream2 {
+    let ty = &field.ty;
+    match (&field.attrs.encoding, wrapper_type(crate_name, field)) {
+        (Some(Encoding::Serde), _) => quote!( #crate_name::serde::deserialize::<#ty, _>(reader)? ),
+        (Some(Encoding::Count(name)), _) => {
+            let count = field_variable(name);
+            match asynchronous {
+                true => quote!( #crate_name::prefixed::read_counted_async::<_, #ty, _>(&#count, reader).await? ),
+                false => quote!( #crate_name::prefixed::read_counted::<_, #ty, _>(&#count, reader)? ),
+            }
+        },
+        (Some(Encoding::SizeBytes(name)), _) => {
+            let size = field_variable(name);
+            match asynchronous {
+                true => quote!( #crate_name::prefixed::read_sized_async::<_, #ty, _>(&#size, reader).await? ),
+                false => quote!( #crate_name::prefixed::read_sized::<_, #ty, _>(&#size, reader)? ),
+            }
+        },
+        (Some(Encoding::Versioned), _) => {
+            let version = nested_version(crate_name, ty);
+            quote!( <#ty as #crate_name::Versioned>::deserialize_versioned(#version, reader)? )
+        },
+        (_, Some(wrapper)) => {
+            let read = read_value(crate_name, wrapper, asynchronous);
+            quote!( #read.into_inner() )
+        },
+        (_, None) => read_value(crate_name, ty, asynchronous),
+    }
+}
+
+// Constructs `path` (a struct or an enum variant) out of the expressions reading each of its fields in order, the
+// fields are read into variables first so later fields can use the lengths stored in earlier ones
+fn construct(path: TokenStream2, fields: &Fields, read: impl Fn(&Field) -> TokenStream2) -> TokenStream2 {
+    let reads = fields.iter().map(read);
+
+    match fields {
+        Fields::Unnamed(fields) => {
+            let variables = (0..fields.len()).map(field_variable).collect::<Vec<_>>();
+            quote!({ #( let #variables = #reads; )* #path(#(#variables),*) })
+        },
+        Fields::Named(fields) => {
+            let names = fields.iter().filter_map(|field| field.ident.as_ref()).collect::<Vec<_>>();
+            let variables = names.iter().map(field_variable).collect::<Vec<_>>();
+            quote!({ #( let #variables = #reads; )* #path { #( #names: #variables ),* } })
+        },
+        Fields::Empty => path,
+    }
+}
+
+// Reads the byte length prefixed payload of a delimited value, the fields are then read out of `reader` synchronously
+fn read_payload(crate_name: &Ident, asynchronous: bool) -> TokenStream2 {
+    let read = read_value(crate_name, quote!(#crate_name::__private::Vec<u8>), asynchronous);
+
+    quote! {
+        let payload = #read;
+        let reader = &mut &payload[..];
+    }
+}
+
+// Reads a field out of the payload of a delimited value, fields missing from the end of the payload are defaulted,
+// which is reported on the field type if it doesn't implement `Default`
+fn read_delimited_field(crate_name: &Ident, field: &Field) -> TokenStream2 {
+    let read = read_field(crate_name, field, false);
+    let ty = &field.ty;
+    let default = quote_spanned!(ty.span()=> <#ty as #crate_name::__private::DefaultWhenMissing>::missing() );
+
+    quote!( if reader.is_empty() { #default } else { #read } )
+}
+
+// Binds every field of a variant to a variable, returning the pattern along with the variables
+fn bind_fields(path: TokenStream2, fields: &Fields) -> (TokenStream2, Vec<Ident>) {
+    match fields {
+        Fields::Unnamed(fields) => {
+            let variables = (0..fields.len()).map(|i| Ident::new(&format!("var{}", i), Span::call_site())).collect::<Vec<_>>();
+            (quote!( #path(#(#variables),*) ), variables)
+        },
+        Fields::Named(fields) => {
+            let names = fields.iter().filter_map(|field| field.ident.clone()).collect::<Vec<_>>();
+            (quote!( #path { #(#names),* } ), names)
+        },
+        Fields::Empty => (path, Vec::new()),
+    }
+}
+
+// Constructs the `other` variant out of the unknown discriminant (and the raw payload of delimited enums)
+fn construct_other(path: TokenStream2, fields: &Fields, delimited: bool) -> TokenStream2 {
+    let values = if delimited { vec![quote!(discriminant), quote!(payload)] } else { vec![quote!(discriminant)] };
+
+    match fields {
+        Fields::Named(fields) => {
+            let names = fields.iter().map(|field| &field.ident);
+            quote!( #path { #( #names: #values ),* } )
+        },
+        _ => quote!( #path(#(#values),*) ),
+    }
+}
+
+// Reads the discriminant and then the fields of the matching variant
+fn deserialize_enum(crate_name: &Ident, identifier: &Ident, repr: &Ident, variants: &[Variant], delimited: bool, asynchronous: bool) -> TokenStream2 {
+    let read = read_value(crate_name, repr, asynchronous);
+    let (other, variants): (Vec<&Variant>, Vec<&Variant>) = variants.iter().partition(|variant| variant.attrs.other);
+
+    // the fields of delimited variants are decoded from their payload, which has already been read entirely, just like
+    // the fields of delimited structs
+    let read_payload = delimited.then(|| read_payload(crate_name, asynchronous));
+
+    let deserialize_arms = variants.iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let body = construct(quote!(#identifier::#ident), &variant.fields, |field| match delimited {
+                true => read_delimited_field(crate_name, field),
+                false => read_field(crate_name, field, asynchronous),
+            });
+
+            quote!(discriminants::#ident => Ok(#body))
+        });
+
+    let struct_impl = variants.iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let discrim = &variant.discriminant;
+            quote!(const #ident: #repr = #discrim;)
+        });
+
+    let fallback = match other.first() {
+        Some(variant) => {
+            let ident = &variant.ident;
+            let body = construct_other(quote!(#identifier::#ident), &variant.fields, delimited);
+            quote!( _ => Ok(#body) )
+        },
+        None => quote!( _ => Err(#crate_name::Error::InvalidEnumVariant) ),
+    };
+
+    quote! {
+        let discriminant: #repr = #read;
+        #read_payload
+
+        struct discriminants;
+
+        #[allow(non_upper_case_globals)]
+        impl discriminants {
+            #(#struct_impl)*
+        }
+
+        match discriminant {
+            #(#deserialize_arms,)*
+            #fallback,
+        }
+    }
+}
+
+// The impl is only kept if `transmittable` itself has the `tokio` feature enabled
+fn impl_async(crate_name: &Ident, ident: &Ident, deserialize: TokenStream2) -> TokenStream2 {
+    quote! {
+        #crate_name::__private::if_tokio! {
+            impl #crate_name::AsyncTransmittable for #ident {
+                async fn deserialize_async<R: #crate_name::asynchronous::AsyncRead + Unpin + Send>(reader: &mut R) -> #crate_name::Result<Self> {
+                    #deserialize
+                }
+            }
+        }
+    }
+}
+
 #[proc_macro]
 pub fn read_and_write(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as TestGeneratorInput);
@@ -76,23 +447,43 @@ pub fn read_and_write(input: TokenStream) -> TokenStream {
                 println!("Serialized {:?}: {:?}", value, inner);
 
                 assert_eq!(inner, expected);
+                assert_eq!(#crate_name::Transmittable::encoded_len(&value), expected.len(), "encoded_len does not match the serialized length");
+                if let Some(size) = <#ty as #crate_name::Transmittable>::FIXED_SIZE {
+                    assert_eq!(size, expected.len(), "FIXED_SIZE does not match the serialized length");
+                }
             }
         }
     })
 }
 
 
-#[proc_macro_derive(Transmittable)]
+#[proc_macro_derive(Transmittable, attributes(transmittable))]
 pub fn transmittable_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as TransmittableInput);
     let crate_name = get_crate_name();
+    let packet_impl = packets::impl_packet(&input, &crate_name);
+    let schema_impl = input.attrs.schema.then(|| impl_schema(&input, &crate_name));
 
     TokenStream::from((match input.data {
         // We can ignore the data, as we pass the whole input itself to the impl function
         Data::Struct(_) => impl_struct(input, crate_name),
         Data::Enum(_) => impl_enum(input, crate_name),
         _ => Err(Error::new(Span::call_site(), "Only structs and enums are supported")),
-    }).unwrap_or_else(|e| e.to_compile_error()))
+    }).map(|tokens| quote!(#tokens #packet_impl #schema_impl)).unwrap_or_else(|e| e.to_compile_error()))
+}
+
+#[proc_macro]
+pub fn packets(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as packets::PacketsInput);
+
+    TokenStream::from(packets::impl_packets(input, get_crate_name()))
+}
+
+#[proc_macro]
+pub fn protocol(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as packets::ProtocolInput);
+
+    TokenStream::from(packets::impl_protocol(input, get_crate_name()))
 }
 
 fn impl_struct(input: TransmittableInput, crate_name: Ident) -> Result<TokenStream2> {
@@ -100,46 +491,255 @@ fn impl_struct(input: TransmittableInput, crate_name: Ident) -> Result<TokenStre
         return Err(Error::new(Span::call_site(), "Expected a struct"));
     };
 
+    if let Fields::Empty = fields {
+        return Err(Error::new(Span::call_site(), "Expected a struct with fields"));
+    }
+
+    check_since(&fields, input.attrs.version)?;
+    check_rest(&fields)?;
+    check_length_fields(&fields)?;
+    check_conditions(&fields)?;
+
     let ident = input.ident;
+    let delimited = input.attrs.delimited;
+    let members = fields.members();
+    let serialize = fields.iter().zip(&members).map(|(field, member)| write_field(&crate_name, field, quote!(&self.#member), &|name| quote!(&self.#name))).collect::<Vec<_>>();
+    let encoded_len = fields.iter().zip(&members).map(|(field, member)| field_len(&crate_name, field, quote!(&self.#member))).collect::<Vec<_>>();
+
+    // delimited structs are prefixed with the byte length of their fields
+    let fixed_size = match delimited {
+        true => {
+            let fixed_size = fixed_size(&crate_name, &fields);
+            quote!( #crate_name::size::sum(&[<usize as #crate_name::Transmittable>::FIXED_SIZE, #fixed_size]) )
+        },
+        false => fixed_size(&crate_name, &fields),
+    };
+    let header_size = delimited.then(|| quote!( ::core::mem::size_of::<usize>() + ));
+    let write_length = delimited.then(|| quote! {
+        let length: usize = 0 #( + #encoded_len )*;
+        #crate_name::Transmittable::serialize(&length, writer)?;
+    });
+
+    // fields added in later versions read `version`, which is the newest one unless decoding a specific version
+    let version = input.attrs.version.map(|version| quote!( let version: u32 = #version; ));
+    // nested versioned fields are written with it as well
+    let write_version = fields.iter()
+        .any(|field| matches!(field.attrs.encoding, Some(Encoding::Versioned)))
+        .then(|| version.clone());
+    let deserialize = deserialize_struct(&crate_name, &fields, delimited, false);
+    let async_impl = match input.attrs.asynchronous {
+        true => {
+            check_async(&fields)?;
+            let deserialize = deserialize_struct(&crate_name, &fields, delimited, true);
+            impl_async(&crate_name, &ident, quote!( #version #deserialize ))
+        },
+        false => TokenStream2::new(),
+    };
 
-    match fields {
-        Fields::Unnamed(count) => {
-            let iter = (0..count).map(|i| syn::Index::from(i as usize));
-            let deserialize = iter.clone().map(|_| quote!( #crate_name::Transmittable::deserialize(reader)? ));
-
-            Ok(quote! {
-                impl #crate_name::Transmittable for #ident {
-                    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> #crate_name::Result<()> {
-                        #( #crate_name::Transmittable::serialize(&self.#iter, writer)?; )*
-                        Ok(())
-                    }
-
-                    fn deserialize<R: std::io::Read>(reader: &mut R) -> #crate_name::Result<Self> {
-                        Ok(Self(#(#deserialize),*))
-                    }
-                }
-            })
-        },
-        Fields::Named(named) => {
-            let idents = named.iter();
-            let deserialize = idents.clone().map(|ident| quote!( #ident: #crate_name::Transmittable::deserialize(reader)? ));
-
-            Ok(quote! {
-                impl #crate_name::Transmittable for #ident {
-                    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> #crate_name::Result<()> {
-                        #( #crate_name::Transmittable::serialize(&self.#idents, writer)?; )*
-                        Ok(())
-                    }
-
-                    fn deserialize<R: std::io::Read>(reader: &mut R) -> #crate_name::Result<Self> {
-                        Ok(Self {
-                            #( #deserialize ),*
-                        })
-                    }
+    let versioned_impl = input.attrs.version
+        .map(|version| impl_versioned(&crate_name, &ident, version, &fields, delimited))
+        .unwrap_or_default();
+
+    Ok(quote! {
+        impl #crate_name::Transmittable for #ident {
+            const FIXED_SIZE: Option<usize> = #fixed_size;
+
+            fn serialize<W: #crate_name::Writer>(&self, writer: &mut W) -> #crate_name::Result<()> {
+                #write_version
+                #write_length
+                #( #serialize )*
+                Ok(())
+            }
+
+            fn deserialize<R: #crate_name::Reader>(reader: &mut R) -> #crate_name::Result<Self> {
+                #version
+                #deserialize
+            }
+
+            fn encoded_len(&self) -> usize {
+                if let Some(size) = Self::FIXED_SIZE {
+                    return size;
                 }
-            })
-        },
-        Fields::Empty => Err(Error::new(Span::call_site(), "Expected a struct with fields")),
+
+                #write_version
+                #header_size 0 #( + #encoded_len )*
+            }
+        }
+
+        #async_impl
+        #versioned_impl
+    })
+}
+
+// Reads the fields of a struct and constructs it, the fields of delimited structs are read out of their payload:
+// bytes following the known fields are skipped, and fields missing from the end of the payload are defaulted
+fn deserialize_struct(crate_name: &Ident, fields: &Fields, delimited: bool, asynchronous: bool) -> TokenStream2 {
+    if !delimited {
+        let construct = construct(quote!(Self), fields, |field| read_field(crate_name, field, asynchronous));
+        return quote!( Ok(#construct) );
+    }
+
+    let read_payload = read_payload(crate_name, asynchronous);
+    let construct = construct(quote!(Self), fields, |field| read_delimited_field(crate_name, field));
+
+    quote! {
+        #read_payload
+        Ok(#construct)
+    }
+}
+
+// Fields can only be added in later versions of versioned structs, and not after the newest version
+fn check_since(fields: &Fields, version: Option<u32>) -> Result<()> {
+    for field in fields.iter() {
+        if version.is_none() && matches!(field.attrs.encoding, Some(Encoding::Versioned)) {
+            return Err(Error::new_spanned(&field.ty, "`versioned` requires `#[transmittable(version = ...)]` on the struct"));
+        }
+
+        let Some(since) = field.attrs.since else {
+            continue;
+        };
+
+        match version {
+            None => return Err(Error::new_spanned(&field.ty, "`since` requires `#[transmittable(version = ...)]` on the struct")),
+            Some(version) if since > version => {
+                return Err(Error::new_spanned(&field.ty, format!("field added in version {since}, but the newest version is {version}")));
+            },
+            Some(_) => (),
+        }
+    }
+
+    Ok(())
+}
+
+// Fields running to the end of the input can't be followed by anything
+fn check_rest(fields: &Fields) -> Result<()> {
+    let count = fields.iter().count();
+
+    match fields.iter().take(count.saturating_sub(1)).find(|field| matches!(field.attrs.encoding, Some(Encoding::Rest))) {
+        Some(field) => Err(Error::new_spanned(&field.ty, "`rest` is only allowed on the last field")),
+        None => Ok(()),
+    }
+}
+
+// Lengths can only be stored in named fields declared before the field they describe
+fn check_length_fields(fields: &Fields) -> Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        let (Some(Encoding::Count(name)) | Some(Encoding::SizeBytes(name))) = &field.attrs.encoding else {
+            continue;
+        };
+
+        check_earlier(fields, i, name)?;
+    }
+
+    Ok(())
+}
+
+// Conditions decide whether `Option` fields are present, and can only use the earlier fields they were decoded from
+fn check_conditions(fields: &Fields) -> Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        let Some(condition) = &field.attrs.condition else {
+            continue;
+        };
+
+        if option_inner(&field.ty).is_none() {
+            return Err(Error::new_spanned(&field.ty, "`if` is only allowed on `Option` fields"));
+        }
+
+        let mut names = Vec::new();
+        let replaced = replace_fields(condition.expr.to_token_stream(), &mut |name| {
+            names.push(name.clone());
+            quote!(())
+        });
+
+        if contains_self(replaced) {
+            return Err(Error::new_spanned(&condition.text, "conditions can only use fields through `self.field`"));
+        }
+
+        for name in &names {
+            check_earlier(fields, i, name)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Types opting into `AsyncTransmittable` can't have fields which can only be decoded synchronously
+fn check_async(fields: &Fields) -> Result<()> {
+    let Some(field) = fields.iter().find(|field| is_sync_only(field)) else {
+        return Ok(());
+    };
+
+    let message = "fields with this attribute can't be decoded asynchronously, which `#[transmittable(async)]` requires";
+    let error = match &field.attrs.encoding_path {
+        Some(path) => Error::new_spanned(path, message),
+        None => Error::new_spanned(&field.ty, message),
+    };
+
+    Err(error)
+}
+
+// Fields can only refer to named fields declared before them, which have already been decoded
+fn check_earlier(fields: &Fields, i: usize, name: &Ident) -> Result<()> {
+    match fields.iter().take(i).any(|earlier| earlier.ident.as_ref() == Some(name)) {
+        true => Ok(()),
+        false => Err(Error::new_spanned(name, format!("`{name}` has to be a named field declared before this one"))),
+    }
+}
+
+// Whether a `self` which isn't followed by a field is left
+fn contains_self(tokens: TokenStream2) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => ident == "self",
+        TokenTree::Group(group) => contains_self(group.stream()),
+        _ => false,
+    })
+}
+
+fn impl_versioned(crate_name: &Ident, ident: &Ident, version: u32, fields: &Fields, delimited: bool) -> TokenStream2 {
+    let deserialize = deserialize_struct(crate_name, fields, delimited, false);
+
+    let (serialize, encoded_len): (Vec<_>, Vec<_>) = fields.iter()
+        .zip(fields.members())
+        .map(|(field, member)| {
+            let serialize = write_field(crate_name, field, quote!(&self.#member), &|name| quote!(&self.#name));
+            let encoded_len = field_len(crate_name, field, quote!(&self.#member));
+
+            match field.attrs.since {
+                Some(since) => (
+                    quote!( if version >= #since { #serialize } ),
+                    quote!( if version >= #since { #encoded_len } else { 0 } ),
+                ),
+                None => (serialize, encoded_len),
+            }
+        })
+        .unzip();
+
+    let header_size = delimited.then(|| quote!( ::core::mem::size_of::<usize>() + ));
+    let write_length = delimited.then(|| quote! {
+        let length: usize = 0 #( + #encoded_len )*;
+        #crate_name::Transmittable::serialize(&length, writer)?;
+    });
+
+    quote! {
+        impl #crate_name::Versioned for #ident {
+            const VERSION: u32 = #version;
+
+            fn serialize_versioned<W: #crate_name::Writer>(&self, version: u32, writer: &mut W) -> #crate_name::Result<()> {
+                #crate_name::versioned::check_version::<Self>(version)?;
+                #write_length
+                #( #serialize )*
+                Ok(())
+            }
+
+            fn deserialize_versioned<R: #crate_name::Reader>(version: u32, reader: &mut R) -> #crate_name::Result<Self> {
+                #crate_name::versioned::check_version::<Self>(version)?;
+                #deserialize
+            }
+
+            fn encoded_len_versioned(&self, version: u32) -> usize {
+                #header_size 0 #( + #encoded_len )*
+            }
+        }
     }
 }
 
@@ -152,6 +752,20 @@ fn impl_enum(input: TransmittableInput, crate_name: Ident) -> Result<TokenStream
         return Err(Error::new(Span::call_site(), "Enums without a repr are not supported."));
     };
 
+    if input.attrs.version.is_some() {
+        return Err(Error::new(Span::call_site(), "Only structs can be versioned"));
+    }
+
+    for variant in &variants {
+        check_since(&variant.fields, None)?;
+        check_rest(&variant.fields)?;
+        check_length_fields(&variant.fields)?;
+        check_conditions(&variant.fields)?;
+    }
+
+    let delimited = input.attrs.delimited;
+    check_other(&variants, delimited)?;
+
     let get_discriminant = {
         #[cfg(feature = "unsafe")]
         quote! { unsafe { *<*const _>::from(self).cast::<#repr>() } }
@@ -165,86 +779,124 @@ fn impl_enum(input: TransmittableInput, crate_name: Ident) -> Result<TokenStream
 
     let identifier = input.ident;
 
+    // the discriminant, followed by the byte length of the fields for delimited enums
+    let header_size = if delimited {
+        quote!( ::core::mem::size_of::<#repr>() + ::core::mem::size_of::<usize>() )
+    } else {
+        quote!( ::core::mem::size_of::<#repr>() )
+    };
+
     let serialize_arms = variants.iter()
-        .filter_map(|variant| {
+        .map(|variant| {
             let ident = &variant.ident;
-
-            match variant.fields.clone() {
-                Fields::Unnamed(count) => {
-                    let iter = 0..count;
-                    let variables = iter.clone().map(|i| Ident::new(&format!("var{}", i), Span::call_site()));
-                    let serialize = variables.clone().map(|var| quote!( #crate_name::Transmittable::serialize(#var, writer)?; ));
-
-                    Some(quote! {
-                        #identifier::#ident(#(#variables),*) => {
-                            #(#serialize)*
-                        }
-                    })
-                },
-                Fields::Named(names) => Some(quote! {
-                    #identifier::#ident {#(#names),*} => {
-                        #(#crate_name::Transmittable::serialize(#names, writer)?;)*
-                    }
-                }),
-                Fields::Empty => None,
+            let (pattern, variables) = bind_fields(quote!(#identifier::#ident), &variant.fields);
+            let serialize = variant.fields.iter().zip(&variables).map(|(field, variable)| write_field(&crate_name, field, variable, &|name| quote!(#name)));
+            let encoded_len = variant.fields.iter().zip(&variables).map(|(field, variable)| field_len(&crate_name, field, variable));
+            let serialize = quote!( #( #serialize )* );
+
+            // the `other` variant holds its own discriminant (and payload)
+            if variant.attrs.other {
+                return quote!( #pattern => { #serialize } );
             }
-        })
-        .collect::<Vec<TokenStream2>>();
 
-    let deserialize_arms = variants.iter()
-        .map(|variant| {
-            let ident = &variant.ident;
+            let length = delimited.then(|| quote! {
+                let length: usize = 0 #( + #encoded_len )*;
+                #crate_name::Transmittable::serialize(&length, writer)?;
+            });
 
-            let body = match variant.fields.clone() {
-                Fields::Unnamed(count) => {
-                    let deserialize = (0..count).map(|_| quote!(#crate_name::Transmittable::deserialize(reader)?));
+            quote! {
+                #pattern => {
+                    #crate_name::Transmittable::serialize(&discriminant, writer)?;
+                    #length
+                    #serialize
+                }
+            }
+        });
 
-                    quote!(#identifier::#ident(#(#deserialize),*))
-                },
-                Fields::Named(names) => quote!(#identifier::#ident {
-                    #(#names: #crate_name::Transmittable::deserialize(reader)?),*
-                }),
-                Fields::Empty => quote!(#identifier::#ident),
-            };
+    let deserialize = deserialize_enum(&crate_name, &identifier, &repr, &variants, delimited, false);
+    let async_impl = match input.attrs.asynchronous {
+        true => {
+            for variant in &variants {
+                check_async(&variant.fields)?;
+            }
 
-            quote!(discriminants::#ident => Ok(#body))
-        });
+            impl_async(&crate_name, &identifier, deserialize_enum(&crate_name, &identifier, &repr, &variants, delimited, true))
+        },
+        false => TokenStream2::new(),
+    };
 
-    let struct_impl = variants.iter()
+    let encoded_len_arms = variants.iter()
         .map(|variant| {
             let ident = &variant.ident;
-            let discrim = &variant.discriminant;
-            quote!(const #ident: #repr = #discrim;)
+            let (pattern, variables) = bind_fields(quote!(#identifier::#ident), &variant.fields);
+            let header = (!variant.attrs.other).then(|| quote!( #header_size + ));
+            let encoded_len = variant.fields.iter().zip(&variables).map(|(field, variable)| field_len(&crate_name, field, variable));
+
+            quote!( #pattern => #header 0 #( + #encoded_len )* )
+        });
+
+    // an enum only has a fixed size if every variant's fields add up to the same size,
+    // the `other` variant only consists of the discriminant unless its payload is kept as well
+    let variant_sizes = variants.iter()
+        .map(|variant| match (variant.attrs.other, delimited) {
+            (false, _) => fixed_size(&crate_name, &variant.fields),
+            (true, false) => quote!( Some(0) ),
+            (true, true) => quote!( None ),
         });
+    let length_size = delimited.then(|| quote!( <usize as #crate_name::Transmittable>::FIXED_SIZE, ));
 
     Ok(quote! {
         impl #crate_name::Transmittable for #identifier {
-            fn serialize<W: std::io::Write>(&self, writer: &mut W) -> #crate_name::Result<()> {
-                #crate_name::Transmittable::serialize(&#get_discriminant, writer)?;
+            const FIXED_SIZE: Option<usize> = #crate_name::size::sum(&[
+                <#repr as #crate_name::Transmittable>::FIXED_SIZE,
+                #length_size
+                #crate_name::size::same(&[#(#variant_sizes),*]),
+            ]);
+
+            fn serialize<W: #crate_name::Writer>(&self, writer: &mut W) -> #crate_name::Result<()> {
+                let discriminant: #repr = #get_discriminant;
 
                 match self {
                     #(#serialize_arms,)*
-                    _ => ()
                 }
 
                 Ok(())
             }
 
-            fn deserialize<R: std::io::Read>(reader: &mut R) -> #crate_name::Result<Self> {
-                let discriminant: #repr = #crate_name::Transmittable::deserialize(reader)?;
-
-                struct discriminants;
+            fn deserialize<R: #crate_name::Reader>(reader: &mut R) -> #crate_name::Result<Self> {
+                #deserialize
+            }
 
-                #[allow(non_upper_case_globals)]
-                impl discriminants {
-                    #(#struct_impl)*
+            fn encoded_len(&self) -> usize {
+                if let Some(size) = Self::FIXED_SIZE {
+                    return size;
                 }
 
-                match discriminant {
-                    #(#deserialize_arms,)*
-                    _ => Err(#crate_name::Error::InvalidEnumVariant),
+                match self {
+                    #(#encoded_len_arms,)*
                 }
             }
         }
+
+        #async_impl
     })
-}
\ No newline at end of file
+}
+
+// At most one variant can catch unknown discriminants, holding the discriminant and, for delimited enums, the payload
+fn check_other(variants: &[Variant], delimited: bool) -> Result<()> {
+    let mut others = variants.iter().filter(|variant| variant.attrs.other);
+
+    if let Some(variant) = others.next() {
+        let (expected, example) = if delimited { (2, "Unknown(u8, Vec<u8>)") } else { (1, "Unknown(u8)") };
+
+        if variant.fields.iter().count() != expected {
+            return Err(Error::new_spanned(&variant.ident, format!("the `other` variant has to hold the unknown discriminant{}, like `{example}`", if delimited { " and payload" } else { "" })));
+        }
+    }
+
+    if let Some(variant) = others.next() {
+        return Err(Error::new_spanned(&variant.ident, "only one variant can be marked as `other`"));
+    }
+
+    Ok(())
+}
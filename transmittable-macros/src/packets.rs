@@ -0,0 +1,335 @@
+use crate::parse::TransmittableInput;
+use crate::{impl_async, read_value};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{braced, parenthesized, Attribute, Type, Visibility};
+
+// enum ServerboundPlay: u8 { KeepAlive(KeepAlivePacket), ... }
+pub struct PacketsInput {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub ident: Ident,
+    pub id_type: Ident,
+    pub variants: Vec<PacketVariant>,
+    pub asynchronous: bool,
+    pub schema: bool,
+}
+
+pub struct PacketVariant {
+    pub attrs: Vec<Attribute>,
+    pub ident: Ident,
+    pub ty: Type,
+}
+
+impl Parse for PacketVariant {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let ident = input.parse()?;
+        let content;
+        parenthesized!(content in input);
+        let ty = content.parse()?;
+
+        Ok(Self {
+            attrs,
+            ident,
+            ty,
+        })
+    }
+}
+
+// Takes the `#[transmittable(...)]` attributes out of the ones placed on a packet set, returning whether it
+// implements `AsyncTransmittable` and `Schema`
+fn parse_packets_attrs(attrs: &mut Vec<Attribute>) -> syn::Result<(bool, bool)> {
+    let mut asynchronous = false;
+    let mut schema = false;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("transmittable")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("async") {
+                asynchronous = true;
+                Ok(())
+            } else if meta.path.is_ident("schema") {
+                schema = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown transmittable attribute"))
+            }
+        })?;
+    }
+
+    attrs.retain(|attr| !attr.path().is_ident("transmittable"));
+    Ok((asynchronous, schema))
+}
+
+impl Parse for PacketsInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let (asynchronous, schema) = parse_packets_attrs(&mut attrs)?;
+        let vis = input.parse()?;
+        input.parse::<syn::Token![enum]>()?;
+        let ident = input.parse()?;
+        input.parse::<syn::Token![:]>()?;
+
+        let id_type: Ident = input.parse()?;
+        if !matches!(id_type.to_string().as_str(), "u8" | "u16" | "u32" | "u64") {
+            return Err(syn::Error::new(id_type.span(), "packet ids have to be one of u8, u16, u32 or u64"));
+        }
+
+        let content;
+        braced!(content in input);
+        let variants = Punctuated::<PacketVariant, Comma>::parse_terminated(&content)?
+            .into_iter()
+            .collect();
+
+        Ok(Self {
+            attrs,
+            vis,
+            ident,
+            id_type,
+            variants,
+            asynchronous,
+            schema,
+        })
+    }
+}
+
+// enum Serverbound(ConnectionState) { Handshake(HandshakePackets), ... }
+pub struct ProtocolInput {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub ident: Ident,
+    pub state: Ident,
+    pub states: Vec<PacketVariant>, // each state along with its packet set
+}
+
+impl Parse for ProtocolInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<syn::Token![enum]>()?;
+        let ident = input.parse()?;
+
+        let state;
+        parenthesized!(state in input);
+        let state = state.parse()?;
+
+        let content;
+        braced!(content in input);
+        let states = Punctuated::<PacketVariant, Comma>::parse_terminated(&content)?
+            .into_iter()
+            .collect();
+
+        Ok(Self {
+            attrs,
+            vis,
+            ident,
+            state,
+            states,
+        })
+    }
+}
+
+// Implements `Packet` for types with a `#[transmittable(packet_id = ...)]` attribute
+pub fn impl_packet(input: &TransmittableInput, crate_name: &Ident) -> TokenStream2 {
+    let Some(packet_id) = &input.attrs.packet_id else {
+        return TokenStream2::new();
+    };
+
+    let ident = &input.ident;
+
+    quote! {
+        impl #crate_name::Packet for #ident {
+            const ID: u64 = #packet_id;
+        }
+    }
+}
+
+// Matches `id` against the ids of the packets and reads the matching one
+fn dispatch(crate_name: &Ident, ident: &Ident, id_type: &Ident, variants: &[PacketVariant], asynchronous: bool) -> TokenStream2 {
+    let variant_idents = variants.iter().map(|variant| &variant.ident).collect::<Vec<_>>();
+    let variant_types = variants.iter().map(|variant| &variant.ty).collect::<Vec<_>>();
+    let reads = variant_types.iter().map(|ty| read_value(crate_name, ty, asynchronous));
+
+    quote! {
+        struct ids;
+
+        #[allow(non_upper_case_globals)]
+        impl ids {
+            #( const #variant_idents: #id_type = <#variant_types as #crate_name::Packet>::ID as #id_type; )*
+        }
+
+        match id {
+            #( ids::#variant_idents => Ok(#ident::#variant_idents(#reads)), )*
+            _ => Err(#crate_name::Error::UnknownPacketId(id as u64)),
+        }
+    }
+}
+
+pub fn impl_packets(input: PacketsInput, crate_name: Ident) -> TokenStream2 {
+    let schema_impl = input.schema.then(|| crate::schema::impl_packets_schema(&input, &crate_name));
+
+    let PacketsInput { attrs, vis, ident, id_type, variants, asynchronous, .. } = input;
+
+    let variant_idents = variants.iter().map(|variant| &variant.ident).collect::<Vec<_>>();
+    let variant_types = variants.iter().map(|variant| &variant.ty).collect::<Vec<_>>();
+    let variant_attrs = variants.iter().map(|variant| &variant.attrs);
+    let duplicate_message = format!("duplicate packet id in `{}`", ident);
+    let overflow_message = format!("packet id does not fit in the `{}` id of `{}`", id_type, ident);
+
+    let deserialize_body = dispatch(&crate_name, &ident, &id_type, &variants, false);
+    let async_impl = asynchronous.then(|| impl_async(&crate_name, &ident, {
+        let read_id = read_value(&crate_name, &id_type, true);
+        let deserialize_body = dispatch(&crate_name, &ident, &id_type, &variants, true);
+
+        quote! {
+            let id: #id_type = #read_id;
+            #deserialize_body
+        }
+    }));
+
+    quote! {
+        #(#attrs)*
+        #vis enum #ident {
+            #( #(#variant_attrs)* #variant_idents(#variant_types), )*
+        }
+
+        // the ids are only known once the `Packet` impls are, so they're validated at compile time by const evaluation
+        const _: () = {
+            let ids: &[u64] = &[#( <#variant_types as #crate_name::Packet>::ID ),*];
+
+            let mut i = 0;
+            while i < ids.len() {
+                assert!(ids[i] <= #id_type::MAX as u64, #overflow_message);
+
+                let mut j = i + 1;
+                while j < ids.len() {
+                    assert!(ids[i] != ids[j], #duplicate_message);
+                    j += 1;
+                }
+
+                i += 1;
+            }
+        };
+
+        impl #crate_name::PacketSet for #ident {
+            type Id = #id_type;
+
+            fn id(&self) -> #id_type {
+                match self {
+                    #( #ident::#variant_idents(_) => <#variant_types as #crate_name::Packet>::ID as #id_type, )*
+                }
+            }
+
+            fn contains_id(id: #id_type) -> bool {
+                false #( || id as u64 == <#variant_types as #crate_name::Packet>::ID )*
+            }
+
+            fn deserialize_body<R: #crate_name::Reader>(id: #id_type, reader: &mut R) -> #crate_name::Result<Self> {
+                #deserialize_body
+            }
+        }
+
+        impl #crate_name::Transmittable for #ident {
+            fn serialize<W: #crate_name::Writer>(&self, writer: &mut W) -> #crate_name::Result<()> {
+                #crate_name::Transmittable::serialize(&#crate_name::PacketSet::id(self), writer)?;
+
+                match self {
+                    #( #ident::#variant_idents(packet) => #crate_name::Transmittable::serialize(packet, writer), )*
+                }
+            }
+
+            fn deserialize<R: #crate_name::Reader>(reader: &mut R) -> #crate_name::Result<Self> {
+                let id: #id_type = #crate_name::Transmittable::deserialize(reader)?;
+                #crate_name::PacketSet::deserialize_body(id, reader)
+            }
+
+            fn encoded_len(&self) -> usize {
+                ::core::mem::size_of::<#id_type>() + match self {
+                    #( #ident::#variant_idents(packet) => #crate_name::Transmittable::encoded_len(packet), )*
+                }
+            }
+        }
+
+        #async_impl
+        #schema_impl
+
+        #(
+            impl From<#variant_types> for #ident {
+                fn from(packet: #variant_types) -> Self {
+                    #ident::#variant_idents(packet)
+                }
+            }
+        )*
+    }
+}
+
+pub fn impl_protocol(input: ProtocolInput, crate_name: Ident) -> TokenStream2 {
+    let ProtocolInput { attrs, vis, ident, state, states } = input;
+
+    let state_idents = states.iter().map(|state| &state.ident).collect::<Vec<_>>();
+    let state_names = state_idents.iter().map(|ident| ident.to_string());
+    let state_attrs = states.iter().map(|state| &state.attrs);
+    let set_types = states.iter().map(|state| &state.ty).collect::<Vec<_>>();
+
+    quote! {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #vis enum #state {
+            #( #state_idents, )*
+        }
+
+        impl #state {
+            pub const fn name(self) -> &'static str {
+                match self {
+                    #( #state::#state_idents => #state_names, )*
+                }
+            }
+        }
+
+        #(#attrs)*
+        #vis enum #ident {
+            #( #(#state_attrs)* #state_idents(#set_types), )*
+        }
+
+        impl #crate_name::Protocol for #ident {
+            type State = #state;
+
+            fn state(&self) -> #state {
+                match self {
+                    #( #ident::#state_idents(_) => #state::#state_idents, )*
+                }
+            }
+
+            fn serialize_in<W: #crate_name::Writer>(&self, state: #state, writer: &mut W) -> #crate_name::Result<()> {
+                let id: u64 = match self {
+                    #( #ident::#state_idents(packets) => #crate_name::PacketSet::id(packets).into(), )*
+                };
+
+                if #crate_name::Protocol::state(self) != state {
+                    return Err(#crate_name::Error::InvalidPacketForState { id, state: state.name() });
+                }
+
+                match self {
+                    #( #ident::#state_idents(packets) => #crate_name::Transmittable::serialize(packets, writer), )*
+                }
+            }
+
+            fn deserialize_in<R: #crate_name::Reader>(state: #state, reader: &mut R) -> #crate_name::Result<Self> {
+                match state {
+                    #(
+                        #state::#state_idents => {
+                            let id: <#set_types as #crate_name::PacketSet>::Id = #crate_name::Transmittable::deserialize(reader)?;
+                            if !<#set_types as #crate_name::PacketSet>::contains_id(id) {
+                                return Err(#crate_name::Error::InvalidPacketForState { id: id.into(), state: state.name() });
+                            }
+
+                            Ok(#ident::#state_idents(#crate_name::PacketSet::deserialize_body(id, reader)?))
+                        },
+                    )*
+                }
+            }
+        }
+    }
+}
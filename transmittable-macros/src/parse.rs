@@ -5,7 +5,7 @@ use syn::spanned::Spanned;
 use syn::token::Comma;
 use syn::BinOp::Add;
 use syn::Expr::{Binary, Lit};
-use syn::{parenthesized, DeriveInput, Expr, ExprBinary, ExprLit, LitInt, Path, Type};
+use syn::{parenthesized, Attribute, DeriveInput, Expr, ExprBinary, ExprLit, Index, LitInt, LitStr, Member, Path, Type};
 
 pub struct TestCase {
     pub serialized: Expr,
@@ -22,14 +22,66 @@ pub struct TestGeneratorInput {
 pub struct TransmittableInput {
     pub ident: Ident,
     pub repr: Option<Ident>,
+    pub attrs: ContainerAttrs,
     pub data: Data,
 }
 
+// The `#[transmittable(...)]` attributes placed on the struct or enum itself
+#[derive(Clone, Default)]
+pub struct ContainerAttrs {
+    pub packet_id: Option<Expr>,
+    pub version: Option<u32>,
+    pub delimited: bool,
+    // whether to implement `AsyncTransmittable`, which every field type has to implement as well
+    pub asynchronous: bool,
+    // whether to implement `Schema`, which every field type has to implement as well
+    pub schema: bool,
+}
+
+// The `#[transmittable(...)]` attributes placed on an enum variant
+#[derive(Clone, Default)]
+pub struct VariantAttrs {
+    pub other: bool,
+}
+
+// The `#[transmittable(...)]` attributes placed on a single field
+#[derive(Clone, Default)]
+pub struct FieldAttrs {
+    pub since: Option<u32>,
+    pub encoding: Option<Encoding>,
+    // the attribute the encoding was chosen with, to report errors about it
+    pub encoding_path: Option<Path>,
+    pub condition: Option<Condition>,
+}
+
+// An expression over earlier fields deciding whether an `Option` field is present, instead of a presence byte
+#[derive(Clone)]
+pub struct Condition {
+    pub text: LitStr,
+    pub expr: Expr,
+}
+
+// An encoding replacing the `Transmittable` implementation of a field's type
+#[derive(Clone)]
+pub enum Encoding {
+    Serde,
+    Len(Type),
+    NulTerminated(Option<LitInt>),
+    Fixed { size: LitInt, pad: Option<Expr> },
+    Rest,
+    // the length is stored in an earlier field, either as the number of items or as the byte length
+    Count(Ident),
+    SizeBytes(Ident),
+    // a nested versioned type, encoded with the version of the containing struct
+    Versioned,
+}
+
 #[derive(Clone)]
 pub struct Variant {
     pub ident: Ident,
     pub fields: Fields,
     pub discriminant: Expr,
+    pub attrs: VariantAttrs,
 }
 
 #[derive(Clone)]
@@ -39,11 +91,38 @@ pub enum Data {
     Unknown,
 }
 
+#[derive(Clone)]
+pub struct Field {
+    pub ident: Option<Ident>, // `None` for unnamed fields
+    pub ty: Type,
+    pub attrs: FieldAttrs,
+}
+
 #[derive(Clone)]
 pub enum Fields {
     Empty,
-    Unnamed(u32), // the number of fields
-    Named(Vec<Ident>), // the names of the fields
+    Unnamed(Vec<Field>),
+    Named(Vec<Field>),
+}
+
+impl Fields {
+    pub fn iter(&self) -> std::slice::Iter<'_, Field> {
+        match self {
+            Fields::Empty => [].iter(),
+            Fields::Unnamed(fields) | Fields::Named(fields) => fields.iter(),
+        }
+    }
+
+    // How each field is accessed on `self`, either by name or by index
+    pub fn members(&self) -> Vec<Member> {
+        self.iter()
+            .enumerate()
+            .map(|(i, field)| match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(i)),
+            })
+            .collect()
+    }
 }
 
 impl Parse for TestCase {
@@ -89,6 +168,7 @@ impl Parse for TestGeneratorInput {
 impl Parse for TransmittableInput {
     fn parse(stream: ParseStream) -> syn::Result<Self> {
         let input = DeriveInput::parse(stream)?;
+        let attrs = parse_container_attrs(&input.attrs)?;
         let repr = match input.data {
             syn::Data::Enum(_) => parse_repr(&input)?, // only check for the repr on enums
             _ => None,
@@ -106,7 +186,7 @@ impl Parse for TransmittableInput {
                     data.variants
                         .iter()
                         .map(|variant| parse_fields(variant.fields.clone())
-                            .map(|fields| {
+                            .and_then(|fields| {
                                 let discriminant = match variant.discriminant {
                                     Some((_, ref expr)) => {
                                         current_discriminant = increment_expr(expr.clone());
@@ -121,11 +201,12 @@ impl Parse for TransmittableInput {
                                 };
 
 
-                                Variant { // we want to keep it as a result so that .collect can return a result as well
+                                Ok(Variant { // we want to keep it as a result so that .collect can return a result as well
                                     ident: variant.ident.clone(),
                                     fields,
                                     discriminant,
-                                }
+                                    attrs: parse_variant_attrs(&variant.attrs)?,
+                                })
                             })
                         )
                         .collect::<syn::Result<Vec<Variant>>>()?
@@ -137,6 +218,7 @@ impl Parse for TransmittableInput {
         Ok(TransmittableInput {
             ident: input.ident,
             repr,
+            attrs,
             data,
         })
     }
@@ -162,15 +244,132 @@ fn parse_fields(input: syn::Fields) -> syn::Result<Fields> {
             .map(|field| field.ident
                 .as_ref()
                 .ok_or_else(|| syn::Error::new(field.span(), "named fields must have an identifier"))
-                .map(|v| v.to_owned())
+                .and_then(|v| Ok(Field {
+                    ident: Some(v.to_owned()),
+                    ty: field.ty.clone(),
+                    attrs: parse_field_attrs(&field.attrs)?,
+                }))
             )
-            .collect::<syn::Result<Vec<Ident>>>()?
+            .collect::<syn::Result<Vec<Field>>>()?
+        ),
+        syn::Fields::Unnamed(fields) => Fields::Unnamed(fields.unnamed
+            .iter()
+            .map(|field| Ok(Field {
+                ident: None,
+                ty: field.ty.clone(),
+                attrs: parse_field_attrs(&field.attrs)?,
+            }))
+            .collect::<syn::Result<Vec<Field>>>()?
         ),
-        syn::Fields::Unnamed(fields) => Fields::Unnamed(fields.unnamed.len() as u32),
         syn::Fields::Unit => Fields::Empty,
     })
 }
 
+fn parse_container_attrs(attrs: &[Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut parsed = ContainerAttrs::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("transmittable")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("packet_id") {
+                parsed.packet_id = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("version") {
+                parsed.version = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                Ok(())
+            } else if meta.path.is_ident("delimited") {
+                parsed.delimited = true;
+                Ok(())
+            } else if meta.path.is_ident("async") {
+                parsed.asynchronous = true;
+                Ok(())
+            } else if meta.path.is_ident("schema") {
+                parsed.schema = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown transmittable attribute"))
+            }
+        })?;
+    }
+
+    Ok(parsed)
+}
+
+fn parse_variant_attrs(attrs: &[Attribute]) -> syn::Result<VariantAttrs> {
+    let mut parsed = VariantAttrs::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("transmittable")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("other") {
+                parsed.other = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown transmittable attribute"))
+            }
+        })?;
+    }
+
+    Ok(parsed)
+}
+
+fn parse_field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut parsed = FieldAttrs::default();
+
+    let mut pad = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("transmittable")) {
+        attr.parse_nested_meta(|meta| {
+            let encoding = if meta.path.is_ident("since") {
+                parsed.since = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                return Ok(());
+            } else if meta.path.is_ident("if") {
+                let text = meta.value()?.parse::<LitStr>()?;
+                parsed.condition = Some(Condition { expr: text.parse()?, text });
+                return Ok(());
+            } else if meta.path.is_ident("pad") {
+                pad = Some((meta.value()?.parse::<Expr>()?, meta.path.span()));
+                return Ok(());
+            } else if meta.path.is_ident("serde") {
+                Encoding::Serde
+            } else if meta.path.is_ident("len") {
+                Encoding::Len(meta.value()?.parse()?)
+            } else if meta.path.is_ident("nul_terminated") {
+                // the maximum length is optional
+                match meta.input.peek(syn::Token![=]) {
+                    true => Encoding::NulTerminated(Some(meta.value()?.parse()?)),
+                    false => Encoding::NulTerminated(None),
+                }
+            } else if meta.path.is_ident("fixed") {
+                Encoding::Fixed { size: meta.value()?.parse()?, pad: None }
+            } else if meta.path.is_ident("rest") {
+                Encoding::Rest
+            } else if meta.path.is_ident("count") {
+                Encoding::Count(meta.value()?.parse::<LitStr>()?.parse()?)
+            } else if meta.path.is_ident("size_bytes") {
+                Encoding::SizeBytes(meta.value()?.parse::<LitStr>()?.parse()?)
+            } else if meta.path.is_ident("versioned") {
+                Encoding::Versioned
+            } else {
+                return Err(meta.error("unknown transmittable attribute"));
+            };
+
+            parsed.encoding_path = Some(meta.path.clone());
+            match parsed.encoding.replace(encoding) {
+                Some(_) => Err(meta.error("only one of `serde`, `len`, `nul_terminated`, `fixed`, `rest`, `count`, `size_bytes` and `versioned` can be used")),
+                None => Ok(()),
+            }
+        })?;
+    }
+
+    if let Some((expr, span)) = pad {
+        match &mut parsed.encoding {
+            Some(Encoding::Fixed { pad, .. }) => *pad = Some(expr),
+            _ => return Err(syn::Error::new(span, "`pad` requires `fixed`")),
+        }
+    }
+
+    Ok(parsed)
+}
+
 fn parse_repr(input: &DeriveInput) -> syn::Result<Option<Ident>> {
     let mut found = None;
 
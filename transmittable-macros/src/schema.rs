@@ -0,0 +1,142 @@
+use crate::packets::PacketsInput;
+use crate::parse::{Data, Encoding, Field, Fields, TransmittableInput};
+use crate::{conditional, wrapper_type};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::quote;
+
+fn field_layout(crate_name: &Ident, field: &Field) -> TokenStream2 {
+    if let Some((condition, inner)) = conditional(field) {
+        let layout = field_layout(crate_name, &inner);
+        let condition = condition.text.value();
+        return quote!( #layout.conditional(#condition) );
+    }
+
+    let ty = &field.ty;
+    match (&field.attrs.encoding, wrapper_type(crate_name, field)) {
+        (Some(Encoding::Serde), _) => quote!( #crate_name::schema::Layout::Opaque(::core::any::type_name::<#ty>().into()) ),
+        (Some(Encoding::Count(field)), _) => {
+            let field = field.to_string();
+            quote!( <#ty as #crate_name::schema::Schema>::schema().with_length(#crate_name::schema::Length::CountField(#field.into())) )
+        },
+        (Some(Encoding::SizeBytes(field)), _) => {
+            let field = field.to_string();
+            quote!( <#ty as #crate_name::schema::Schema>::schema().with_length(#crate_name::schema::Length::SizeField(#field.into())) )
+        },
+        (_, Some(wrapper)) => quote!( <#wrapper as #crate_name::schema::Schema>::schema() ),
+        (_, None) => quote!( <#ty as #crate_name::schema::Schema>::schema() ),
+    }
+}
+
+fn field_layouts(crate_name: &Ident, fields: &Fields) -> Vec<TokenStream2> {
+    let fields = match fields {
+        Fields::Empty => return Vec::new(),
+        Fields::Unnamed(fields) | Fields::Named(fields) => fields,
+    };
+
+    fields.iter()
+        .enumerate()
+        .map(|(i, field)| {
+            // tuple fields are named after their index
+            let name = field.ident.as_ref().map_or_else(|| i.to_string(), Ident::to_string);
+            let layout = field_layout(crate_name, field);
+            let since = field.attrs.since.map(|since| quote!( .since(#since) ));
+
+            quote!( #crate_name::schema::FieldLayout::new(#name, #layout) #since )
+        })
+        .collect()
+}
+
+// The impl is only kept if `transmittable` itself has the `schema` feature enabled
+fn impl_schema_trait(crate_name: &Ident, ident: &Ident, layout: TokenStream2) -> TokenStream2 {
+    quote! {
+        #crate_name::__private::if_schema! {
+            impl #crate_name::schema::Schema for #ident {
+                fn schema() -> #crate_name::schema::Layout {
+                    #layout
+                }
+            }
+        }
+    }
+}
+
+pub fn impl_schema(input: &TransmittableInput, crate_name: &Ident) -> TokenStream2 {
+    let ident = &input.ident;
+    let name = ident.to_string();
+
+    let delimited = input.attrs.delimited.then(|| quote! {
+        .delimited(#crate_name::schema::Length::Prefixed(#crate_name::schema::Integer::new(false, usize::BITS)))
+    });
+
+    let layout = match &input.data {
+        Data::Struct(fields) => {
+            let fields = field_layouts(crate_name, fields);
+            quote!( #crate_name::schema::Layout::Struct(#crate_name::schema::StructLayout::new(#name, [#(#fields),*])#delimited) )
+        },
+        Data::Enum(variants) => {
+            // enums without a repr are rejected when generating the `Transmittable` impl
+            let Some(repr) = &input.repr else {
+                return TokenStream2::new();
+            };
+
+            let signed = repr.to_string().starts_with('i');
+            // the `other` variant doesn't have a tag of its own, it's recorded as the catch-all instead
+            let catch_all = variants.iter()
+                .find(|variant| variant.attrs.other)
+                .map(|variant| {
+                    let name = variant.ident.to_string();
+                    quote!( .catch_all(#name) )
+                });
+            let variants = variants.iter()
+                .filter(|variant| !variant.attrs.other)
+                .map(|variant| {
+                    let name = variant.ident.to_string();
+                    let discriminant = &variant.discriminant;
+                    let fields = field_layouts(crate_name, &variant.fields);
+
+                    quote! {
+                        #crate_name::schema::VariantLayout::new(#name, { const TAG: #repr = #discriminant; TAG as i128 }, [#(#fields),*])
+                    }
+                });
+
+            quote! {
+                #crate_name::schema::Layout::Enum(#crate_name::schema::EnumLayout::new(
+                    #name,
+                    #crate_name::schema::Integer::new(#signed, #repr::BITS),
+                    [#(#variants),*],
+                )#delimited #catch_all)
+            }
+        },
+        Data::Unknown => return TokenStream2::new(),
+    };
+
+    impl_schema_trait(crate_name, ident, layout)
+}
+
+// Packet sets are described as an enum tagged by the packet ids
+pub fn impl_packets_schema(input: &PacketsInput, crate_name: &Ident) -> TokenStream2 {
+    let ident = &input.ident;
+    let id_type = &input.id_type;
+    let name = ident.to_string();
+
+    let variants = input.variants.iter()
+        .map(|variant| {
+            let name = variant.ident.to_string();
+            let ty = &variant.ty;
+
+            quote! {
+                #crate_name::schema::VariantLayout::new(
+                    #name,
+                    <#ty as #crate_name::Packet>::ID as i128,
+                    [#crate_name::schema::FieldLayout::new("0", <#ty as #crate_name::schema::Schema>::schema())],
+                )
+            }
+        });
+
+    impl_schema_trait(crate_name, ident, quote! {
+        #crate_name::schema::Layout::Enum(#crate_name::schema::EnumLayout::new(
+            #name,
+            #crate_name::schema::Integer::new(false, #id_type::BITS),
+            [#(#variants),*],
+        ))
+    })
+}
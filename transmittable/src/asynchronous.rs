@@ -0,0 +1,150 @@
+//! Asynchronous serialization over tokio's [`AsyncRead`] and [`AsyncWrite`], enabled by the `tokio` feature.
+//!
+//! Every type in this crate implementing [`Transmittable`] implements [`AsyncTransmittable`] as well. Derived types
+//! opt in with `#[transmittable(async)]` (which `packets!` accepts too), as every field then has to implement
+//! [`AsyncTransmittable`]: implementing it for every derived type would break the ones with fields which don't as soon
+//! as any crate enables the feature. The attribute does nothing while the feature is disabled, so libraries can use it
+//! without depending on tokio.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::ops::{Bound, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use crate::{Transmittable, Result};
+
+pub use tokio::io::{AsyncRead, AsyncWrite};
+
+pub trait AsyncTransmittable: Transmittable + Sized + Send + Sync {
+    /// Serializes the value into a buffer and writes it with a single `write_all`,
+    /// as encoding never has to wait for anything.
+    fn serialize_async<W: AsyncWrite + Unpin + Send>(&self, writer: &mut W) -> impl Future<Output = Result<()>> + Send {
+        async move {
+            let buf = crate::to_vec(self)?;
+            writer.write_all(&buf).await?;
+            Ok(())
+        }
+    }
+
+    /// Reads the value from the reader, awaiting until enough bytes are available.
+    fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> impl Future<Output = Result<Self>> + Send;
+}
+
+// Types with a fixed size are read all at once and then decoded by their synchronous implementation
+macro_rules! impl_fixed {
+    ($($ty:ty),*) => {$(
+        impl AsyncTransmittable for $ty {
+            async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+                let mut buf = [0u8; size_of::<Self>()];
+                reader.read_exact(&mut buf).await?;
+                Transmittable::deserialize(&mut buf.as_slice())
+            }
+        }
+    )*};
+}
+
+impl_fixed!(
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize,
+    f32, f64, bool
+);
+
+impl<T: AsyncTransmittable, const N: usize> AsyncTransmittable for [T; N] {
+    async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+        let size = usize::deserialize_async(reader).await?;
+        if size != N {
+            return Err(crate::Error::LengthMismatch { expected: N, actual: size });
+        }
+
+        let mut buf: Vec<T> = Vec::with_capacity(size);
+
+        for _ in 0..size {
+            buf.push(T::deserialize_async(reader).await?);
+        }
+
+        buf.try_into().map_err(|_| unreachable!("Failed to convert a Vec<T> (of size N) to a [T; N]"))
+    }
+}
+
+impl<T: AsyncTransmittable> AsyncTransmittable for Vec<T> {
+    async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+        let size = usize::deserialize_async(reader).await?;
+        let mut buffer = Vec::with_capacity(size);
+
+        for _ in 0..size {
+            buffer.push(T::deserialize_async(reader).await?);
+        }
+
+        Ok(buffer)
+    }
+}
+
+impl AsyncTransmittable for String {
+    async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+        let size = usize::deserialize_async(reader).await?;
+        let mut buf = alloc::vec![0u8; size];
+        reader.read_exact(&mut buf).await?;
+        Ok(String::from_utf8(buf)?)
+    }
+}
+
+impl<T: AsyncTransmittable> AsyncTransmittable for Option<T> {
+    async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+        if bool::deserialize_async(reader).await? {
+            Ok(Some(T::deserialize_async(reader).await?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: AsyncTransmittable> AsyncTransmittable for Range<T> {
+    async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+        let start = T::deserialize_async(reader).await?;
+        let end = T::deserialize_async(reader).await?;
+        Ok(start..end)
+    }
+}
+
+impl<T: AsyncTransmittable> AsyncTransmittable for RangeInclusive<T> {
+    async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+        let start = T::deserialize_async(reader).await?;
+        let end = T::deserialize_async(reader).await?;
+        Ok(start..=end)
+    }
+}
+
+impl<T: AsyncTransmittable> AsyncTransmittable for RangeFrom<T> {
+    async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+        Ok(T::deserialize_async(reader).await?..)
+    }
+}
+
+impl<T: AsyncTransmittable> AsyncTransmittable for RangeTo<T> {
+    async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+        Ok(..T::deserialize_async(reader).await?)
+    }
+}
+
+impl<T: AsyncTransmittable> AsyncTransmittable for RangeToInclusive<T> {
+    async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+        Ok(..=T::deserialize_async(reader).await?)
+    }
+}
+
+impl AsyncTransmittable for RangeFull {
+    async fn deserialize_async<R: AsyncRead + Unpin + Send>(_reader: &mut R) -> Result<Self> {
+        Ok(..)
+    }
+}
+
+impl<T: AsyncTransmittable> AsyncTransmittable for Bound<T> {
+    async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+        match u8::deserialize_async(reader).await? {
+            0 => Ok(Bound::Included(T::deserialize_async(reader).await?)),
+            1 => Ok(Bound::Excluded(T::deserialize_async(reader).await?)),
+            2 => Ok(Bound::Unbounded),
+            _ => Err(crate::Error::InvalidEnumVariant),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Support for the [`bytes`](::bytes) crate, enabled by the `bytes` feature.
+//!
+//! [`Bytes`] and [`BytesMut`] are encoded exactly like a `Vec<u8>`: an `usize` length prefix followed by the bytes.
+//! [`BytesReader`] and [`BytesWriter`] adapt any [`Buf`] or [`BufMut`] into a [`Reader`] or [`Writer`], which is what
+//! [`from_buf`] and [`to_buf`] use, so values can be decoded straight out of a network buffer without going through
+//! `std::io` adapters.
+//!
+//! Decoding through a [`Reader`] always copies, as the reader can't hand out shared slices of its input. This includes
+//! `Bytes` fields of derived types and values decoded with [`from_buf`] or asynchronously, even when the source is a
+//! [`Bytes`] itself: only [`split_bytes`] avoids the copy, decoding a `Bytes` value as a zero-copy slice of its source.
+
+use ::bytes::{Buf, BufMut, Bytes, BytesMut};
+use crate::{Error, Reader, Result, Transmittable, Writer};
+
+impl Transmittable for Bytes {
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Transmittable::serialize(&self.len(), writer)?;
+        writer.write_all(self)?;
+        Ok(())
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        Ok(BytesMut::deserialize(reader)?.freeze())
+    }
+
+    fn encoded_len(&self) -> usize {
+        size_of::<usize>() + self.len()
+    }
+}
+
+impl Transmittable for BytesMut {
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Transmittable::serialize(&self.len(), writer)?;
+        writer.write_all(self)?;
+        Ok(())
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let size = Transmittable::deserialize(reader)?;
+        let mut buffer = BytesMut::zeroed(size);
+        reader.read_exact(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn encoded_len(&self) -> usize {
+        size_of::<usize>() + self.len()
+    }
+}
+
+/// Decodes a length-prefixed byte buffer from the front of `src` without copying it.
+///
+/// The returned [`Bytes`] shares the allocation of `src`, which is advanced past the decoded value. Nothing is
+/// consumed if `src` ends before the whole buffer.
+pub fn split_bytes(src: &mut Bytes) -> Result<Bytes> {
+    let (size, read) = crate::from_slice::<usize>(src)?;
+    if src.len() - read < size {
+        return Err(Error::UnexpectedEof);
+    }
+
+    src.advance(read);
+    Ok(src.split_to(size))
+}
+
+/// Serializes the value into `buf`, failing if it runs out of space.
+pub fn to_buf<T: Transmittable, B: BufMut>(value: &T, buf: B) -> Result<()> {
+    value.serialize(&mut BytesWriter::new(buf))
+}
+
+/// Deserializes a value from the front of `buf`, advancing it past the bytes read.
+pub fn from_buf<T: Transmittable, B: Buf>(buf: &mut B) -> Result<T> {
+    T::deserialize(&mut BytesReader::new(buf))
+}
+
+/// A [`Reader`] consuming the bytes of a [`Buf`].
+#[derive(Debug)]
+pub struct BytesReader<B> {
+    buf: B,
+}
+
+impl<B: Buf> BytesReader<B> {
+    pub fn new(buf: B) -> Self {
+        Self { buf }
+    }
+
+    pub fn get_ref(&self) -> &B {
+        &self.buf
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+
+    fn read_buf(&mut self, buf: &mut [u8]) -> usize {
+        let amount = buf.len().min(self.buf.remaining());
+        self.buf.copy_to_slice(&mut buf[..amount]);
+        amount
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: Buf> std::io::Read for BytesReader<B> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Ok(self.read_buf(buf))
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<B: Buf> Reader for BytesReader<B> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        Ok(self.read_buf(buf))
+    }
+}
+
+/// A [`Writer`] appending to a [`BufMut`].
+#[derive(Debug)]
+pub struct BytesWriter<B> {
+    buf: B,
+}
+
+impl<B: BufMut> BytesWriter<B> {
+    pub fn new(buf: B) -> Self {
+        Self { buf }
+    }
+
+    pub fn get_ref(&self) -> &B {
+        &self.buf
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+
+    fn write_buf(&mut self, buf: &[u8]) -> usize {
+        let amount = buf.len().min(self.buf.remaining_mut());
+        self.buf.put_slice(&buf[..amount]);
+        amount
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: BufMut> std::io::Write for BytesWriter<B> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Ok(self.write_buf(buf))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<B: BufMut> Writer for BytesWriter<B> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        if self.write_buf(buf) < buf.len() {
+            return Err(Error::WriteZero);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod asynchronous {
+    use ::bytes::{Bytes, BytesMut};
+    use tokio::io::AsyncReadExt;
+    use crate::asynchronous::{AsyncRead, AsyncTransmittable};
+    use crate::Result;
+
+    impl AsyncTransmittable for Bytes {
+        async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+            Ok(BytesMut::deserialize_async(reader).await?.freeze())
+        }
+    }
+
+    impl AsyncTransmittable for BytesMut {
+        async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+            let size = usize::deserialize_async(reader).await?;
+            let mut buffer = BytesMut::zeroed(size);
+            reader.read_exact(&mut buffer).await?;
+            Ok(buffer)
+        }
+    }
+}
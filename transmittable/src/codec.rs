@@ -0,0 +1,99 @@
+//! A [`tokio_util::codec`] implementation for streams of [`Transmittable`] messages, enabled by the `tokio-util` feature.
+//!
+//! Every message is prefixed with its length (a big endian `u32` by default, see [`FrameConfig`]),
+//! so partial frames can be detected without attempting to decode them.
+//! This allows plugging message types straight into [`tokio_util::codec::Framed`].
+
+use bytes::{Buf, BufMut, BytesMut};
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use tokio_util::codec::{Decoder, Encoder};
+use crate::{Error, FrameConfig, HeaderWidth, Result, Transmittable};
+
+/// A codec encoding and decoding `T`s, each prefixed with its length.
+#[derive(Debug)]
+pub struct TransmittableCodec<T> {
+    config: FrameConfig,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> TransmittableCodec<T> {
+    pub fn new() -> Self {
+        Self::with_config(FrameConfig::default())
+    }
+
+    /// Creates a codec with a `u32` header which rejects frames longer than `max_frame_length` bytes (excluding the header),
+    /// both when encoding and decoding.
+    pub fn with_max_frame_length(max_frame_length: usize) -> Self {
+        Self::with_config(FrameConfig::new(HeaderWidth::U32, max_frame_length))
+    }
+
+    pub fn with_config(config: FrameConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn config(&self) -> &FrameConfig {
+        &self.config
+    }
+}
+
+impl<T> Default for TransmittableCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for TransmittableCodec<T> {
+    fn clone(&self) -> Self {
+        Self::with_config(self.config)
+    }
+}
+
+impl<T: Transmittable> Decoder for TransmittableCodec<T> {
+    type Item = T;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>> {
+        let header_len = self.config.header.size();
+        let Some(mut header) = src.get(..header_len) else {
+            return Ok(None);
+        };
+
+        let len = self.config.header.read_len(&mut header)?;
+        self.config.check_len(len)?;
+
+        if src.len() < header_len + len {
+            // make sure the rest of the frame fits in a single read
+            src.reserve(header_len + len - src.len());
+            return Ok(None);
+        }
+
+        src.advance(header_len);
+        let frame = src.split_to(len);
+        crate::from_slice_exact(&frame).map(Some)
+    }
+}
+
+impl<T: Transmittable, I: Borrow<T>> Encoder<I> for TransmittableCodec<T> {
+    type Error = Error;
+
+    fn encode(&mut self, item: I, dst: &mut BytesMut) -> Result<()> {
+        let item = item.borrow();
+        let len = item.encoded_len();
+        self.config.check_len(len)?;
+
+        // drop the partial frame if the item fails to serialize, so the next one isn't appended to it
+        let start = dst.len();
+        dst.reserve(self.config.header.size() + len);
+        let mut writer = dst.writer();
+        let result = self.config.header.write_len(len, &mut writer).and_then(|()| item.serialize(&mut writer));
+        if result.is_err() {
+            dst.truncate(start);
+        }
+
+        result
+    }
+}
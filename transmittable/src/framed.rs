@@ -0,0 +1,215 @@
+//! Length-delimited framing of [`Transmittable`] messages over readers and writers.
+//!
+//! Every frame consists of a header holding the length of the message, followed by the encoded message itself.
+//! On the reading side the whole frame is read before decoding, and the message has to consume it exactly.
+
+use alloc::vec;
+use core::marker::PhantomData;
+use crate::{Error, Reader, Result, Transmittable, Writer};
+
+/// The integer type the frame length is encoded as (big endian, like every other integer).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HeaderWidth {
+    U8,
+    U16,
+    U32,
+    U64,
+}
+
+impl HeaderWidth {
+    /// The size of the header in bytes.
+    pub const fn size(self) -> usize {
+        match self {
+            HeaderWidth::U8 => 1,
+            HeaderWidth::U16 => 2,
+            HeaderWidth::U32 => 4,
+            HeaderWidth::U64 => 8,
+        }
+    }
+
+    /// The longest frame the header is able to describe.
+    pub const fn max_frame_len(self) -> usize {
+        match self {
+            HeaderWidth::U8 => u8::MAX as usize,
+            HeaderWidth::U16 => u16::MAX as usize,
+            HeaderWidth::U32 if usize::BITS < 32 => usize::MAX,
+            HeaderWidth::U32 => u32::MAX as usize,
+            HeaderWidth::U64 => usize::MAX,
+        }
+    }
+
+    pub(crate) fn write_len<W: Writer>(self, len: usize, writer: &mut W) -> Result<()> {
+        match self {
+            HeaderWidth::U8 => Transmittable::serialize(&(len as u8), writer),
+            HeaderWidth::U16 => Transmittable::serialize(&(len as u16), writer),
+            HeaderWidth::U32 => Transmittable::serialize(&(len as u32), writer),
+            HeaderWidth::U64 => Transmittable::serialize(&(len as u64), writer),
+        }
+    }
+
+    pub(crate) fn read_len<R: Reader>(self, reader: &mut R) -> Result<usize> {
+        // lengths which don't fit in an usize are saturated, they'll be rejected as too large anyway
+        Ok(match self {
+            HeaderWidth::U8 => u8::deserialize(reader)? as usize,
+            HeaderWidth::U16 => u16::deserialize(reader)? as usize,
+            HeaderWidth::U32 => usize::try_from(u32::deserialize(reader)?).unwrap_or(usize::MAX),
+            HeaderWidth::U64 => usize::try_from(u64::deserialize(reader)?).unwrap_or(usize::MAX),
+        })
+    }
+}
+
+/// Describes how frames are laid out and how large they're allowed to be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FrameConfig {
+    pub header: HeaderWidth,
+    /// The maximum length of a frame, excluding the header. Checked both when writing and reading.
+    pub max_frame_len: usize,
+}
+
+impl FrameConfig {
+    /// The default maximum frame length, 8 MiB.
+    pub const DEFAULT_MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+    pub const fn new(header: HeaderWidth, max_frame_len: usize) -> Self {
+        Self { header, max_frame_len }
+    }
+
+    /// The effective maximum frame length, also taking the header width into account.
+    pub const fn limit(&self) -> usize {
+        let header_max = self.header.max_frame_len();
+        if self.max_frame_len < header_max { self.max_frame_len } else { header_max }
+    }
+
+    pub(crate) fn check_len(&self, len: usize) -> Result<()> {
+        match self.limit() {
+            max if len > max => Err(Error::FrameTooLarge { len, max }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A `u32` header and frames of up to 8 MiB.
+impl Default for FrameConfig {
+    fn default() -> Self {
+        Self::new(HeaderWidth::U32, Self::DEFAULT_MAX_FRAME_LEN)
+    }
+}
+
+/// Writes the value as a single frame.
+pub fn write_framed<W: Writer, T: Transmittable>(writer: &mut W, value: &T, config: &FrameConfig) -> Result<()> {
+    let len = value.encoded_len();
+    config.check_len(len)?;
+
+    config.header.write_len(len, writer)?;
+    value.serialize(writer)
+}
+
+/// Reads a single frame and decodes the value from it.
+///
+/// Fails with [`Error::TrailingBytes`] if the value didn't consume the whole frame.
+pub fn read_framed<R: Reader, T: Transmittable>(reader: &mut R, config: &FrameConfig) -> Result<T> {
+    let len = config.header.read_len(reader)?;
+    read_frame_body(reader, len, config)
+}
+
+fn read_frame_body<R: Reader, T: Transmittable>(reader: &mut R, len: usize, config: &FrameConfig) -> Result<T> {
+    config.check_len(len)?;
+
+    let mut frame = vec![0u8; len];
+    reader.read_exact(&mut frame)?;
+    crate::from_slice_exact(&frame)
+}
+
+/// Reads consecutive frames from a reader, yielding the decoded values.
+///
+/// Iteration stops once the reader ends cleanly at a frame boundary, while ending in the middle of a frame is an error.
+#[derive(Debug)]
+pub struct FramedReader<R, T> {
+    reader: R,
+    config: FrameConfig,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: Reader, T: Transmittable> FramedReader<R, T> {
+    pub fn new(reader: R) -> Self {
+        Self::with_config(reader, FrameConfig::default())
+    }
+
+    pub fn with_config(reader: R, config: FrameConfig) -> Self {
+        Self {
+            reader,
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Reads the next frame, returning `Ok(None)` if the reader ended before it.
+    pub fn read(&mut self) -> Result<Option<T>> {
+        // the first byte is read on its own, so that the end of the input can be told apart from a truncated header
+        let mut header = [0u8; 8];
+        let header = &mut header[..self.config.header.size()];
+
+        #[allow(clippy::useless_conversion)] // only useless without `std`, where the reader already returns our error
+        match self.reader.read_exact(&mut header[..1]).map_err(Error::from) {
+            Err(e) if e.is_eof() => return Ok(None),
+            result => result?,
+        }
+
+        self.reader.read_exact(&mut header[1..])?;
+        let len = self.config.header.read_len(&mut &header[..])?;
+        read_frame_body(&mut self.reader, len, &self.config).map(Some)
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Reader, T: Transmittable> Iterator for FramedReader<R, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read().transpose()
+    }
+}
+
+/// Writes values to a writer, each as a separate frame.
+#[derive(Debug)]
+pub struct FramedWriter<W> {
+    writer: W,
+    config: FrameConfig,
+}
+
+impl<W: Writer> FramedWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_config(writer, FrameConfig::default())
+    }
+
+    pub fn with_config(writer: W, config: FrameConfig) -> Self {
+        Self { writer, config }
+    }
+
+    pub fn write<T: Transmittable>(&mut self, value: &T) -> Result<()> {
+        write_framed(&mut self.writer, value, &self.config)
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
@@ -1,15 +1,30 @@
-use std::io::{Read, Write};
-use crate::{Transmittable, Result};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::{Bound, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+use crate::{size, Reader, Transmittable, Result, Writer};
+
+#[cfg(feature = "arrayvec")]
+mod arrayvec;
+#[cfg(feature = "bitflags")]
+mod bitflags;
+#[cfg(feature = "indexmap")]
+mod indexmap;
+#[cfg(feature = "smallvec")]
+mod smallvec;
+#[cfg(feature = "uuid")]
+mod uuid;
 
 macro_rules! impl_byte {
     ($($ty:ty),*) => {$(
         impl Transmittable for $ty {
-            fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+            const FIXED_SIZE: Option<usize> = Some(size_of::<Self>());
+
+            fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
                 writer.write_all(&[*self as u8])?;
                 Ok(())
             }
 
-            fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+            fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
                 let mut buf = [0u8; 1];
                 reader.read_exact(&mut buf)?;
                 Ok(buf[0] as Self)
@@ -27,12 +42,14 @@ impl_byte!(u8, i8);
 macro_rules! impl_integer {
     ($($ty:ty),*) => {$(
         impl Transmittable for $ty {
-            fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+            const FIXED_SIZE: Option<usize> = Some(size_of::<Self>());
+
+            fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
                 writer.write_all(self.to_be_bytes().as_slice())?;
                 Ok(())
             }
 
-            fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+            fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
                 let mut buf = [0u8; size_of::<Self>()];
                 reader.read_exact(&mut buf)?;
                 Ok(Self::from_be_bytes(buf))
@@ -48,12 +65,14 @@ impl_integer!(
 );
 
 impl Transmittable for bool {
-    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
         writer.write_all(&[if *self { 1 } else { 0 }])?;
         Ok(())
     }
 
-    fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
         let mut buf = [0u8; 1];
         reader.read_exact(&mut buf)?;
 
@@ -71,7 +90,9 @@ impl Transmittable for bool {
 
 // TODO: This is bad for performance in a case where the elements are small and there is a lot of them, for example when reading a byte array
 impl<T: Transmittable, const N: usize> Transmittable for [T; N] {
-    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+    const FIXED_SIZE: Option<usize> = size::sum(&[Some(size_of::<usize>()), size::repeat(T::FIXED_SIZE, N)]);
+
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
         Transmittable::serialize(&N, writer)?;
 
         for item in self {
@@ -81,9 +102,13 @@ impl<T: Transmittable, const N: usize> Transmittable for [T; N] {
         Ok(())
     }
 
-    fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
         // TODO: Allocate a slice instead, possibly with MaybeUninit?
         let size: usize = Transmittable::deserialize(reader)?;
+        if size != N {
+            return Err(crate::Error::LengthMismatch { expected: N, actual: size });
+        }
+
         let mut buf: Vec<T> = Vec::with_capacity(size);
 
         for _ in 0..size {
@@ -93,10 +118,14 @@ impl<T: Transmittable, const N: usize> Transmittable for [T; N] {
 
         buf.try_into().map_err(|_| unreachable!("Failed to convert a Vec<T> (of size N) to a [T; N]"))
     }
+
+    fn encoded_len(&self) -> usize {
+        size_of::<usize>() + self.iter().map(Transmittable::encoded_len).sum::<usize>()
+    }
 }
 
 impl<T: Transmittable> Transmittable for Vec<T> {
-    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
         Transmittable::serialize(&self.len(), writer)?;
 
         for item in self {
@@ -106,7 +135,7 @@ impl<T: Transmittable> Transmittable for Vec<T> {
         Ok(())
     }
 
-    fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
         let size = Transmittable::deserialize(reader)?;
         let mut buffer = Vec::with_capacity(size);
 
@@ -117,23 +146,34 @@ impl<T: Transmittable> Transmittable for Vec<T> {
 
         Ok(buffer)
     }
+
+    fn encoded_len(&self) -> usize {
+        size_of::<usize>() + match T::FIXED_SIZE {
+            Some(size) => size * self.len(),
+            None => self.iter().map(Transmittable::encoded_len).sum(),
+        }
+    }
 }
 
 impl Transmittable for String {
-    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
         // TODO: Use slices instead, without having to create a vector
         Transmittable::serialize(&self.as_bytes().to_vec(), writer)?;
         Ok(())
     }
 
-    fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
         let buf = Transmittable::deserialize(reader)?;
         Ok(String::from_utf8(buf)?)
     }
+
+    fn encoded_len(&self) -> usize {
+        size_of::<usize>() + self.len()
+    }
 }
 
 impl<T: Transmittable> Transmittable for Option<T> {
-    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
         Transmittable::serialize(&self.is_some(), writer)?;
 
         if let Some(item) = self {
@@ -143,7 +183,7 @@ impl<T: Transmittable> Transmittable for Option<T> {
         Ok(())
     }
 
-    fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
         let is_present: bool = Transmittable::deserialize(reader)?;
         if is_present {
             Ok(Some(Transmittable::deserialize(reader)?))
@@ -151,4 +191,150 @@ impl<T: Transmittable> Transmittable for Option<T> {
             Ok(None)
         }
     }
+
+    fn encoded_len(&self) -> usize {
+        1 + self.as_ref().map_or(0, Transmittable::encoded_len)
+    }
+}
+
+// Ranges are encoded just like a struct with the same fields would be, in declaration order
+impl<T: Transmittable> Transmittable for Range<T> {
+    const FIXED_SIZE: Option<usize> = size::sum(&[T::FIXED_SIZE, T::FIXED_SIZE]);
+
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Transmittable::serialize(&self.start, writer)?;
+        Transmittable::serialize(&self.end, writer)?;
+        Ok(())
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let start = Transmittable::deserialize(reader)?;
+        let end = Transmittable::deserialize(reader)?;
+        Ok(start..end)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.start.encoded_len() + self.end.encoded_len()
+    }
+}
+
+// NOTE: The `exhausted` flag of an inclusive range is not transmitted
+impl<T: Transmittable> Transmittable for RangeInclusive<T> {
+    const FIXED_SIZE: Option<usize> = size::sum(&[T::FIXED_SIZE, T::FIXED_SIZE]);
+
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Transmittable::serialize(self.start(), writer)?;
+        Transmittable::serialize(self.end(), writer)?;
+        Ok(())
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let start = Transmittable::deserialize(reader)?;
+        let end = Transmittable::deserialize(reader)?;
+        Ok(start..=end)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.start().encoded_len() + self.end().encoded_len()
+    }
+}
+
+impl<T: Transmittable> Transmittable for RangeFrom<T> {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Transmittable::serialize(&self.start, writer)
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        Ok(Transmittable::deserialize(reader)?..)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.start.encoded_len()
+    }
+}
+
+impl<T: Transmittable> Transmittable for RangeTo<T> {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Transmittable::serialize(&self.end, writer)
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        Ok(..Transmittable::deserialize(reader)?)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.end.encoded_len()
+    }
+}
+
+impl<T: Transmittable> Transmittable for RangeToInclusive<T> {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Transmittable::serialize(&self.end, writer)
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        Ok(..=Transmittable::deserialize(reader)?)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.end.encoded_len()
+    }
+}
+
+// A full range carries no data, so it's encoded as nothing at all
+impl Transmittable for RangeFull {
+    const FIXED_SIZE: Option<usize> = Some(0);
+
+    fn serialize<W: Writer>(&self, _writer: &mut W) -> Result<()> {
+        Ok(())
+    }
+
+    fn deserialize<R: Reader>(_reader: &mut R) -> Result<Self> {
+        Ok(..)
+    }
+}
+
+// `Bound` is encoded as if it was a derived `#[repr(u8)]` enum
+impl<T: Transmittable> Transmittable for Bound<T> {
+    const FIXED_SIZE: Option<usize> = size::sum(&[Some(1), size::same(&[T::FIXED_SIZE, T::FIXED_SIZE, Some(0)])]);
+
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            Bound::Included(item) => {
+                Transmittable::serialize(&0u8, writer)?;
+                Transmittable::serialize(item, writer)?;
+            },
+            Bound::Excluded(item) => {
+                Transmittable::serialize(&1u8, writer)?;
+                Transmittable::serialize(item, writer)?;
+            },
+            Bound::Unbounded => Transmittable::serialize(&2u8, writer)?,
+        }
+
+        Ok(())
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let discriminant: u8 = Transmittable::deserialize(reader)?;
+
+        match discriminant {
+            0 => Ok(Bound::Included(Transmittable::deserialize(reader)?)),
+            1 => Ok(Bound::Excluded(Transmittable::deserialize(reader)?)),
+            2 => Ok(Bound::Unbounded),
+            _ => Err(crate::Error::InvalidEnumVariant),
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        1 + match self {
+            Bound::Included(item) | Bound::Excluded(item) => item.encoded_len(),
+            Bound::Unbounded => 0,
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,60 @@
+use ::arrayvec::ArrayVec;
+use crate::{Error, Reader, Result, Transmittable, Writer};
+
+// Encoded just like a `Vec<T>`, longer sequences are rejected before reading any of their items
+impl<T: Transmittable, const CAP: usize> Transmittable for ArrayVec<T, CAP> {
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Transmittable::serialize(&self.len(), writer)?;
+
+        for item in self {
+            Transmittable::serialize(item, writer)?;
+        }
+
+        Ok(())
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let size = Transmittable::deserialize(reader)?;
+        if size > CAP {
+            return Err(Error::CapacityExceeded { len: size, capacity: CAP });
+        }
+
+        let mut buffer = ArrayVec::new();
+        for _ in 0..size {
+            let item = Transmittable::deserialize(reader)?;
+            buffer.push(item);
+        }
+
+        Ok(buffer)
+    }
+
+    fn encoded_len(&self) -> usize {
+        size_of::<usize>() + match T::FIXED_SIZE {
+            Some(size) => size * self.len(),
+            None => self.iter().map(Transmittable::encoded_len).sum(),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod asynchronous {
+    use ::arrayvec::ArrayVec;
+    use crate::asynchronous::{AsyncRead, AsyncTransmittable};
+    use crate::{Error, Result};
+
+    impl<T: AsyncTransmittable, const CAP: usize> AsyncTransmittable for ArrayVec<T, CAP> {
+        async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+            let size = usize::deserialize_async(reader).await?;
+            if size > CAP {
+                return Err(Error::CapacityExceeded { len: size, capacity: CAP });
+            }
+
+            let mut buffer = ArrayVec::new();
+            for _ in 0..size {
+                buffer.push(T::deserialize_async(reader).await?);
+            }
+
+            Ok(buffer)
+        }
+    }
+}
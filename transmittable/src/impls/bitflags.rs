@@ -0,0 +1,56 @@
+/// Implements [`Transmittable`](crate::Transmittable) for types generated by the `bitflags!` macro, enabled by the
+/// `bitflags` feature. `AsyncTransmittable` and `Schema` are implemented as well when the `tokio` and `schema`
+/// features are enabled.
+///
+/// The flags are encoded as their underlying integer. Unknown bits are kept when decoding, so flags added by newer
+/// peers survive a round trip through older ones.
+///
+/// ```ignore
+/// bitflags::bitflags! {
+///     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///     struct Permissions: u8 {
+///         const READ = 1;
+///         const WRITE = 1 << 1;
+///     }
+/// }
+///
+/// transmittable::impl_bitflags!(Permissions);
+/// ```
+#[macro_export]
+macro_rules! impl_bitflags {
+    ($($ty:ty),+ $(,)?) => {$(
+        impl $crate::Transmittable for $ty {
+            const FIXED_SIZE: ::core::option::Option<usize> =
+                <<$ty as $crate::__private::Flags>::Bits as $crate::Transmittable>::FIXED_SIZE;
+
+            fn serialize<W: $crate::Writer>(&self, writer: &mut W) -> $crate::Result<()> {
+                $crate::Transmittable::serialize(&$crate::__private::Flags::bits(self), writer)
+            }
+
+            fn deserialize<R: $crate::Reader>(reader: &mut R) -> $crate::Result<Self> {
+                let bits = $crate::Transmittable::deserialize(reader)?;
+                ::core::result::Result::Ok(<$ty as $crate::__private::Flags>::from_bits_retain(bits))
+            }
+        }
+
+        $crate::__private::if_tokio! {
+            impl $crate::AsyncTransmittable for $ty {
+                async fn deserialize_async<R>(reader: &mut R) -> $crate::Result<Self>
+                where
+                    R: $crate::asynchronous::AsyncRead + ::core::marker::Unpin + ::core::marker::Send,
+                {
+                    let bits = <<$ty as $crate::__private::Flags>::Bits as $crate::AsyncTransmittable>::deserialize_async(reader).await?;
+                    ::core::result::Result::Ok(<$ty as $crate::__private::Flags>::from_bits_retain(bits))
+                }
+            }
+        }
+
+        $crate::__private::if_schema! {
+            impl $crate::schema::Schema for $ty {
+                fn schema() -> $crate::schema::Layout {
+                    <<$ty as $crate::__private::Flags>::Bits as $crate::schema::Schema>::schema()
+                }
+            }
+        }
+    )+};
+}
@@ -0,0 +1,77 @@
+use ::indexmap::IndexMap;
+use core::hash::{BuildHasher, Hash};
+use crate::{Error, Reader, Result, Transmittable, Writer};
+
+// Encoded like a `Vec` of key-value pairs in insertion order, which decoding preserves. Keys appearing more than once
+// are rejected, as the map would otherwise silently end up with fewer entries than were sent
+impl<K, V, S> Transmittable for IndexMap<K, V, S>
+where
+    K: Transmittable + Hash + Eq,
+    V: Transmittable,
+    S: BuildHasher + Default,
+{
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Transmittable::serialize(&self.len(), writer)?;
+
+        for (key, value) in self {
+            Transmittable::serialize(key, writer)?;
+            Transmittable::serialize(value, writer)?;
+        }
+
+        Ok(())
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let size = Transmittable::deserialize(reader)?;
+        let mut map = IndexMap::with_capacity_and_hasher(size, S::default());
+
+        for _ in 0..size {
+            let key = Transmittable::deserialize(reader)?;
+            let value = Transmittable::deserialize(reader)?;
+            insert(&mut map, key, value)?;
+        }
+
+        Ok(map)
+    }
+
+    fn encoded_len(&self) -> usize {
+        size_of::<usize>() + self.iter().map(|(key, value)| key.encoded_len() + value.encoded_len()).sum::<usize>()
+    }
+}
+
+// Every entry read so far added a new key, so the entry being inserted is the `map.len()`th one
+fn insert<K: Hash + Eq, V, S: BuildHasher>(map: &mut IndexMap<K, V, S>, key: K, value: V) -> Result<()> {
+    let entry = map.len();
+    match map.insert(key, value) {
+        Some(_) => Err(Error::DuplicateKey(entry)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod asynchronous {
+    use ::indexmap::IndexMap;
+    use core::hash::{BuildHasher, Hash};
+    use crate::asynchronous::{AsyncRead, AsyncTransmittable};
+    use crate::Result;
+
+    impl<K, V, S> AsyncTransmittable for IndexMap<K, V, S>
+    where
+        K: AsyncTransmittable + Hash + Eq,
+        V: AsyncTransmittable,
+        S: BuildHasher + Default + Send + Sync,
+    {
+        async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+            let size = usize::deserialize_async(reader).await?;
+            let mut map = IndexMap::with_capacity_and_hasher(size, S::default());
+
+            for _ in 0..size {
+                let key = K::deserialize_async(reader).await?;
+                let value = V::deserialize_async(reader).await?;
+                super::insert(&mut map, key, value)?;
+            }
+
+            Ok(map)
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use ::smallvec::{Array, SmallVec};
+use crate::{Reader, Result, Transmittable, Writer};
+
+// Encoded just like a `Vec<T>`, regardless of the inline capacity
+impl<A: Array> Transmittable for SmallVec<A> where A::Item: Transmittable {
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Transmittable::serialize(&self.len(), writer)?;
+
+        for item in self {
+            Transmittable::serialize(item, writer)?;
+        }
+
+        Ok(())
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let size = Transmittable::deserialize(reader)?;
+        let mut buffer = SmallVec::with_capacity(size);
+
+        for _ in 0..size {
+            let item = Transmittable::deserialize(reader)?;
+            buffer.push(item);
+        }
+
+        Ok(buffer)
+    }
+
+    fn encoded_len(&self) -> usize {
+        size_of::<usize>() + match A::Item::FIXED_SIZE {
+            Some(size) => size * self.len(),
+            None => self.iter().map(Transmittable::encoded_len).sum(),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod asynchronous {
+    use ::smallvec::{Array, SmallVec};
+    use crate::asynchronous::{AsyncRead, AsyncTransmittable};
+    use crate::Result;
+
+    impl<A: Array + Send + Sync> AsyncTransmittable for SmallVec<A> where A::Item: AsyncTransmittable {
+        async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+            let size = usize::deserialize_async(reader).await?;
+            let mut buffer = SmallVec::with_capacity(size);
+
+            for _ in 0..size {
+                buffer.push(A::Item::deserialize_async(reader).await?);
+            }
+
+            Ok(buffer)
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use ::uuid::Uuid;
+use crate::{Reader, Result, Transmittable, Writer};
+
+// The 16 bytes of the UUID in their usual order, which is also how the big endian `u128` is laid out
+impl Transmittable for Uuid {
+    const FIXED_SIZE: Option<usize> = Some(16);
+
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(self.as_bytes())?;
+        Ok(())
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let mut buf = [0u8; 16];
+        reader.read_exact(&mut buf)?;
+        Ok(Uuid::from_bytes(buf))
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod asynchronous {
+    use ::uuid::Uuid;
+    use tokio::io::AsyncReadExt;
+    use crate::asynchronous::{AsyncRead, AsyncTransmittable};
+    use crate::Result;
+
+    impl AsyncTransmittable for Uuid {
+        async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+            let mut buf = [0u8; 16];
+            reader.read_exact(&mut buf).await?;
+            Ok(Uuid::from_bytes(buf))
+        }
+    }
+}
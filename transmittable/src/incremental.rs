@@ -0,0 +1,135 @@
+//! A push-based decoder for non-blocking sockets and event loops.
+//!
+//! [`Transmittable::deserialize`] reads straight from the reader, so a `WouldBlock` halfway through a value
+//! loses the bytes which were already read. [`IncrementalDecoder`] instead buffers the incoming bytes
+//! and only hands out values once they were received completely, without requiring an async runtime.
+//!
+//! Values can't be decoded partially, so every attempt starts over from the first unconsumed byte. An attempt is only
+//! made once more bytes were fed since the last one, but a value fed in many small chunks is still parsed once per
+//! chunk, which is quadratic in its size. Large messages are better sent in frames (see [`crate::framed`]), whose
+//! header says how many bytes to wait for.
+//!
+//! Values running to the end of the input, like [`Remaining`](crate::Remaining) and fields marked with
+//! `#[transmittable(rest)]`, can't be decoded this way: they're read successfully out of whatever was received so
+//! far, so the decoder hands them out truncated. They have to be sent in frames as well.
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use crate::{Result, Transmittable};
+
+/// Accepts chunks of bytes as they arrive and yields complete `T`s,
+/// either through [`decode`](Self::decode) or by iterating over it after every [`feed`](Self::feed).
+///
+/// `T` can't end with a [`Remaining`](crate::Remaining), which would be handed out with only the bytes received so far.
+#[derive(Debug)]
+pub struct IncrementalDecoder<T> {
+    buffer: Vec<u8>,
+    // the start of the bytes which weren't consumed yet, compacted lazily
+    position: usize,
+    // the amount of unconsumed bytes the last attempt failed with, so we don't retry until more arrive
+    insufficient: Option<usize>,
+    // set once the input turned out to be malformed, which ends the iteration
+    failed: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Transmittable> IncrementalDecoder<T> {
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            position: 0,
+            insufficient: None,
+            failed: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Appends the bytes to the internal buffer.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        if self.position > 0 && self.position >= self.buffer.len() / 2 {
+            self.buffer.drain(..self.position);
+            self.position = 0;
+        }
+
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// The bytes which were fed but not consumed by a decoded value yet.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer[self.position..]
+    }
+
+    /// Attempts to decode the next value, returning `Ok(None)` if more bytes have to be fed first.
+    ///
+    /// Errors other than running out of bytes mean the input is malformed, in which case the offending bytes are left
+    /// in the buffer and decoding them again fails the same way. Iterating over the decoder stops after such an error.
+    pub fn decode(&mut self) -> Result<Option<T>> {
+        let available = self.buffer.len() - self.position;
+        if self.insufficient == Some(available) || T::FIXED_SIZE.is_some_and(|size| size > available) {
+            return Ok(None);
+        }
+
+        match crate::from_slice(self.buffered()) {
+            Ok((value, read)) => {
+                self.position += read;
+                self.insufficient = None;
+                Ok(Some(value))
+            },
+            Err(e) if e.is_eof() => {
+                self.insufficient = Some(available);
+                Ok(None)
+            },
+            Err(e) => {
+                self.failed = true;
+                Err(e)
+            },
+        }
+    }
+
+    /// Returns whether decoding failed because of malformed input.
+    pub fn has_failed(&self) -> bool {
+        self.failed
+    }
+
+    /// Reads everything that's currently available from a non-blocking reader into the buffer.
+    ///
+    /// Returns the amount of bytes read, where `Ok(0)` means the reader reached its end.
+    /// If no bytes were available at all, the `WouldBlock` error is returned as is.
+    #[cfg(feature = "std")]
+    pub fn read_from<R: std::io::Read>(&mut self, reader: &mut R) -> Result<usize> {
+        let mut chunk = [0u8; 4096];
+        let mut total = 0;
+
+        loop {
+            match reader.read(&mut chunk) {
+                Ok(0) => return Ok(total),
+                Ok(read) => {
+                    self.feed(&chunk[..read]);
+                    total += read;
+                },
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock && total > 0 => return Ok(total),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+impl<T: Transmittable> Default for IncrementalDecoder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Transmittable> Iterator for IncrementalDecoder<T> {
+    type Item = Result<T>;
+
+    // the error is only yielded once, as it would be yielded forever otherwise
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        self.decode().transpose()
+    }
+}
@@ -0,0 +1,133 @@
+//! The reader and writer traits [`Transmittable`](crate::Transmittable) operates on.
+//!
+//! With the `std` feature enabled (the default) these are simply re-exports of [`std::io::Read`] and [`std::io::Write`].
+//! Without it, this module defines minimal replacements which are implemented for byte slices and [`Vec<u8>`].
+
+#[cfg(feature = "std")]
+pub use std::io::{Read as Reader, Write as Writer};
+
+#[cfg(not(feature = "std"))]
+pub use no_std::{Reader, Writer};
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+    use alloc::vec::Vec;
+    use crate::{Error, Result};
+
+    /// A source of bytes, the `no_std` counterpart of `std::io::Read`.
+    pub trait Reader {
+        /// Reads some bytes into `buf`, returning how many were read. Returning `Ok(0)` means the end of the input was reached.
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+        /// Fills the whole `buf`, failing with [`Error::UnexpectedEof`] if the input ends first.
+        fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.read(buf)? {
+                    0 => return Err(Error::UnexpectedEof),
+                    read => buf = &mut buf[read..],
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    /// A sink for bytes, the `no_std` counterpart of `std::io::Write`.
+    pub trait Writer {
+        /// Writes the whole `buf`, failing with [`Error::WriteZero`] if the writer runs out of space.
+        fn write_all(&mut self, buf: &[u8]) -> Result<()>;
+    }
+
+    impl Reader for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let amount = buf.len().min(self.len());
+            let (head, tail) = self.split_at(amount);
+            buf[..amount].copy_from_slice(head);
+            *self = tail;
+            Ok(amount)
+        }
+    }
+
+    impl<R: Reader + ?Sized> Reader for &mut R {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+            (**self).read_exact(buf)
+        }
+    }
+
+    // Just like `std`, writing advances the slice past the written bytes
+    impl Writer for &mut [u8] {
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            if buf.len() > self.len() {
+                return Err(Error::WriteZero);
+            }
+
+            let (head, tail) = core::mem::take(self).split_at_mut(buf.len());
+            head.copy_from_slice(buf);
+            *self = tail;
+            Ok(())
+        }
+    }
+
+    impl Writer for Vec<u8> {
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            self.extend_from_slice(buf);
+            Ok(())
+        }
+    }
+
+    impl<W: Writer + ?Sized> Writer for &mut W {
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            (**self).write_all(buf)
+        }
+    }
+
+    // The tests in `crate::tests` need `std`, these run with `--no-default-features`
+    #[cfg(test)]
+    mod tests {
+        use alloc::string::String;
+        use alloc::vec::Vec;
+        use crate::{Error, Transmittable};
+        use super::{Reader, Writer};
+
+        #[test]
+        fn slice_reader() {
+            let mut reader = &[1u8, 2, 3][..];
+            let mut buf = [0u8; 2];
+            assert_eq!(reader.read(&mut buf), Ok(2));
+            assert_eq!(buf, [1, 2]);
+            assert_eq!(reader, [3]);
+
+            assert_eq!(reader.read_exact(&mut buf), Err(Error::UnexpectedEof));
+            assert_eq!(reader.read(&mut buf), Ok(0));
+        }
+
+        #[test]
+        fn slice_writer() {
+            let mut buf = [0u8; 3];
+            let mut writer = &mut buf[..];
+            assert_eq!(writer.write_all(&[1, 2]), Ok(()));
+            assert_eq!(writer.len(), 1);
+
+            // nothing is written if the bytes don't fit
+            assert_eq!(writer.write_all(&[3, 4]), Err(Error::WriteZero));
+            assert_eq!(writer.len(), 1);
+            assert_eq!(buf, [1, 2, 0]);
+        }
+
+        #[test]
+        fn round_trip() {
+            let mut buf = Vec::new();
+            0x1234u16.serialize(&mut buf).unwrap();
+            String::from("hi").serialize(&mut &mut buf).unwrap();
+
+            let mut reader = &buf[..];
+            assert_eq!(u16::deserialize(&mut reader), Ok(0x1234));
+            assert_eq!(String::deserialize(&mut &mut reader).as_deref(), Ok("hi"));
+            assert_eq!(u8::deserialize(&mut reader), Err(Error::UnexpectedEof));
+        }
+    }
+}
@@ -1,42 +1,277 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 extern crate core;
 
+pub mod framed;
 mod impls;
+pub mod incremental;
+#[cfg(feature = "tokio")]
+pub mod asynchronous;
+#[cfg(feature = "bytes")]
+pub mod bytes;
+#[cfg(feature = "tokio-util")]
+pub mod codec;
+pub mod io;
+pub mod packet;
+pub mod prefixed;
+pub mod remaining;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod seq;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod size;
+pub mod strings;
+pub mod versioned;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests;
 
-use std::io::{Read, Write};
+use alloc::vec::Vec;
 use thiserror::Error;
 
+#[cfg(feature = "tokio")]
+pub use asynchronous::AsyncTransmittable;
+#[cfg(feature = "tokio-util")]
+pub use codec::TransmittableCodec;
+pub use framed::{read_framed, write_framed, FrameConfig, FramedReader, FramedWriter, HeaderWidth};
+pub use incremental::IncrementalDecoder;
+pub use packet::{Packet, PacketSet, Protocol};
+pub use prefixed::{LengthPrefix, Prefixed};
+pub use remaining::{Remaining, Rest};
+#[cfg(feature = "schema")]
+pub use schema::Schema;
+pub use seq::{write_seq, SeqReader, SeqWriter};
+pub use strings::{FixedStr, NulTerminated};
+pub use versioned::Versioned;
+pub use io::{Reader, Writer};
 pub use transmittable_macros::*;
 
+// Items used by the code generated by the macros, which can't rely on the crates in scope
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec::Vec;
+    #[cfg(feature = "bitflags")]
+    pub use bitflags::Flags;
+    pub use crate::__if_tokio as if_tokio;
+    pub use crate::__if_schema as if_schema;
+
+    // Fields of delimited types missing from the end of the payload are defaulted, this only explains why they need
+    // to implement `Default`
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` has to implement `Default` to be a field of a delimited type",
+        label = "fields missing from the end of the payload are defaulted",
+        note = "implement `Default` for `{Self}`, or remove `#[transmittable(delimited)]`"
+    )]
+    pub trait DefaultWhenMissing: Sized {
+        fn missing() -> Self;
+    }
+
+    impl<T: Default> DefaultWhenMissing for T {
+        fn missing() -> Self {
+            T::default()
+        }
+    }
+}
+
+// Keeps the `AsyncTransmittable` impls of derived types only when the `tokio` feature is enabled, so types can opt in
+// without requiring it
+#[cfg(feature = "tokio")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_tokio {
+    ($($tokens:tt)*) => { $($tokens)* };
+}
+
+#[cfg(not(feature = "tokio"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_tokio {
+    ($($tokens:tt)*) => {};
+}
+
+// The same for the `Schema` impls and the `schema` feature
+#[cfg(feature = "schema")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_schema {
+    ($($tokens:tt)*) => { $($tokens)* };
+}
+
+#[cfg(not(feature = "schema"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_schema {
+    ($($tokens:tt)*) => {};
+}
+
+/// The errors encoding and decoding can fail with.
+///
+/// Running out of input and out of space are reported as [`Error::UnexpectedEof`] and [`Error::WriteZero`] with or
+/// without `std`, I/O errors of other kinds are kept in [`Error::IOError`]. Variants only exist with the features
+/// producing them, which is why the enum is non-exhaustive.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
+    #[error("unexpected end of input")]
+    UnexpectedEof,
+    #[error("failed to write the whole buffer")]
+    WriteZero,
+    #[cfg(feature = "std")]
     #[error(transparent)]
-    IOError(#[from] std::io::Error),
+    IOError(std::io::Error),
     #[error("failed to decode UTF-8 string")]
-    Utf8DecodeError(#[from] std::string::FromUtf8Error),
+    Utf8DecodeError(#[from] alloc::string::FromUtf8Error),
     #[error("invalid boolean (expected 0 or 1, got {0})")]
     InvalidBoolean(u8),
     #[error("invalid enum variant")]
     InvalidEnumVariant,
+    #[error("buffer too small (needed {needed} bytes, got {available})")]
+    BufferTooSmall { needed: usize, available: usize },
+    #[error("{0} trailing bytes left after decoding")]
+    TrailingBytes(usize),
+    #[error("frame too large ({len} bytes, the maximum is {max})")]
+    FrameTooLarge { len: usize, max: usize },
+    #[error("length mismatch (expected {expected} items, got {actual})")]
+    LengthMismatch { expected: usize, actual: usize },
+    #[error("capacity exceeded ({len} items, the capacity is {capacity})")]
+    CapacityExceeded { len: usize, capacity: usize },
+    #[error("length {len} doesn't fit in the length prefix (the maximum is {max})")]
+    LengthOverflow { len: usize, max: usize },
+    #[error("string too long ({len} bytes, the maximum is {max})")]
+    StringTooLong { len: usize, max: usize },
+    #[error("no NUL terminator within {max} bytes")]
+    Unterminated { max: usize },
+    #[error("interior NUL byte at position {0}")]
+    InteriorNul(usize),
+    #[error("entry {0} repeats the key of an earlier entry")]
+    DuplicateKey(usize),
+    #[error("conditional field is {}, which disagrees with its condition", if *present { "present" } else { "missing" })]
+    ConditionMismatch { present: bool },
+    #[error("unknown packet id {0:#x}")]
+    UnknownPacketId(u64),
+    #[error("packet id {id:#x} is not valid in the {state} state")]
+    InvalidPacketForState { id: u64, state: &'static str },
+    #[error("unsupported version {version} (the newest is {max})")]
+    UnsupportedVersion { version: u32, max: u32 },
+    #[cfg(feature = "serde")]
+    #[error("{0}")]
+    Serde(alloc::string::String),
 }
 
 impl PartialEq for Error {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
+            (Error::UnexpectedEof,       Error::UnexpectedEof)       => true,
+            (Error::WriteZero,           Error::WriteZero)           => true,
+            #[cfg(feature = "std")]
             (Error::IOError(e1),         Error::IOError(e2))         => e1.kind() == e2.kind() && e1.to_string() == e2.to_string(),
             (Error::Utf8DecodeError(e1), Error::Utf8DecodeError(e2)) => e1 == e2,
             (Error::InvalidBoolean(b1),  Error::InvalidBoolean(b2))  => b1 == b2,
             (Error::InvalidEnumVariant,  Error::InvalidEnumVariant)  => true,
+            (Error::BufferTooSmall { needed: n1, available: a1 }, Error::BufferTooSmall { needed: n2, available: a2 }) => n1 == n2 && a1 == a2,
+            (Error::TrailingBytes(n1),   Error::TrailingBytes(n2))   => n1 == n2,
+            (Error::FrameTooLarge { len: l1, max: m1 }, Error::FrameTooLarge { len: l2, max: m2 }) => l1 == l2 && m1 == m2,
+            (Error::LengthMismatch { expected: e1, actual: a1 }, Error::LengthMismatch { expected: e2, actual: a2 }) => e1 == e2 && a1 == a2,
+            (Error::CapacityExceeded { len: l1, capacity: c1 }, Error::CapacityExceeded { len: l2, capacity: c2 }) => l1 == l2 && c1 == c2,
+            (Error::LengthOverflow { len: l1, max: m1 }, Error::LengthOverflow { len: l2, max: m2 }) => l1 == l2 && m1 == m2,
+            (Error::StringTooLong { len: l1, max: m1 }, Error::StringTooLong { len: l2, max: m2 }) => l1 == l2 && m1 == m2,
+            (Error::Unterminated { max: m1 }, Error::Unterminated { max: m2 }) => m1 == m2,
+            (Error::InteriorNul(p1),     Error::InteriorNul(p2))     => p1 == p2,
+            (Error::DuplicateKey(i1),    Error::DuplicateKey(i2))    => i1 == i2,
+            (Error::ConditionMismatch { present: p1 }, Error::ConditionMismatch { present: p2 }) => p1 == p2,
+            (Error::UnknownPacketId(i1), Error::UnknownPacketId(i2)) => i1 == i2,
+            (Error::InvalidPacketForState { id: i1, state: s1 }, Error::InvalidPacketForState { id: i2, state: s2 }) => i1 == i2 && s1 == s2,
+            (Error::UnsupportedVersion { version: v1, max: m1 }, Error::UnsupportedVersion { version: v2, max: m2 }) => v1 == v2 && m1 == m2,
+            #[cfg(feature = "serde")]
+            (Error::Serde(m1),           Error::Serde(m2))           => m1 == m2,
             _ => false,
         }
     }
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::UnexpectedEof => Error::UnexpectedEof,
+            std::io::ErrorKind::WriteZero => Error::WriteZero,
+            _ => Error::IOError(error),
+        }
+    }
+}
+
+impl Error {
+    /// Returns whether the error was caused by the input ending before the whole value could be read.
+    pub fn is_eof(&self) -> bool {
+        matches!(self, Error::UnexpectedEof)
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
 
 pub trait Transmittable {
-    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()>;
-    fn deserialize<R: Read>(reader: &mut R) -> Result<Self> where Self: Sized;
+    /// The number of bytes every value of this type encodes to, or `None` if it depends on the value.
+    const FIXED_SIZE: Option<usize> = None;
+
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()>;
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> where Self: Sized;
+
+    /// Returns the exact number of bytes `serialize` would write, without allocating a buffer.
+    ///
+    /// The default implementation serializes the value into a writer which only counts the bytes,
+    /// if serialization fails midway the number of bytes written up to that point is returned.
+    ///
+    /// The length is only meaningful for values which can be serialized: for the others, it can be anything and
+    /// shouldn't be used as a length prefix. Callers writing the value afterwards, like [`to_vec`], still get the
+    /// error from `serialize`, as does anything prefixing values with their length as long as the value itself is
+    /// written after the prefix.
+    fn encoded_len(&self) -> usize {
+        if let Some(size) = Self::FIXED_SIZE {
+            return size;
+        }
+
+        let mut counter = size::SizeCounter::default();
+        let _ = self.serialize(&mut counter);
+        counter.count()
+    }
+}
+
+/// Serializes the value into a newly allocated vector, sized with [`Transmittable::encoded_len`].
+pub fn to_vec<T: Transmittable>(value: &T) -> Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(value.encoded_len());
+    value.serialize(&mut buf)?;
+    Ok(buf)
+}
+
+/// Serializes the value into the beginning of `buf`, returning the amount of bytes written.
+///
+/// Fails with [`Error::BufferTooSmall`] without writing anything if the value doesn't fit.
+pub fn to_slice<T: Transmittable>(value: &T, buf: &mut [u8]) -> Result<usize> {
+    let needed = value.encoded_len();
+    if needed > buf.len() {
+        return Err(Error::BufferTooSmall { needed, available: buf.len() });
+    }
+
+    let available = buf.len();
+    let mut writer = buf;
+    value.serialize(&mut writer)?;
+    Ok(available - writer.len())
+}
+
+/// Deserializes a value from the beginning of `buf`, returning it along with the amount of bytes read.
+pub fn from_slice<T: Transmittable>(buf: &[u8]) -> Result<(T, usize)> {
+    let mut reader = buf;
+    let value = T::deserialize(&mut reader)?;
+    Ok((value, buf.len() - reader.len()))
+}
+
+/// Deserializes a value from `buf`, failing with [`Error::TrailingBytes`] if it wasn't consumed entirely.
+pub fn from_slice_exact<T: Transmittable>(buf: &[u8]) -> Result<T> {
+    let (value, read) = from_slice(buf)?;
+    match buf.len() - read {
+        0 => Ok(value),
+        trailing => Err(Error::TrailingBytes(trailing)),
+    }
 }
\ No newline at end of file
@@ -0,0 +1,76 @@
+//! Dispatching between many packet types, each identified by a numeric id.
+//!
+//! Packet types are declared by deriving [`Transmittable`] with a `#[transmittable(packet_id = ...)]` attribute,
+//! which implements [`Packet`] for them. The [`packets!`](crate::packets) macro then declares an enum over a set of them,
+//! which writes the id before the packet and dispatches on it when decoding:
+//!
+//! ```ignore
+//! packets! {
+//!     #[derive(Debug)]
+//!     pub enum Serverbound: u8 {
+//!         KeepAlive(KeepAlive),
+//!         Chat(ChatMessage),
+//!     }
+//! }
+//! ```
+//!
+//! Duplicate ids, as well as ids too large for the declared id type, are rejected at compile time. Packet sets
+//! implement [`AsyncTransmittable`](crate::AsyncTransmittable) when annotated with `#[transmittable(async)]`, like
+//! derived types.
+//!
+//! Protocols where the meaning of an id depends on the state of the connection declare a packet set per state,
+//! and tie them together with the [`protocol!`](crate::protocol) macro. It generates an enum of the states
+//! and an enum over the packet sets implementing [`Protocol`], which decodes with the current state as context:
+//!
+//! ```ignore
+//! protocol! {
+//!     #[derive(Debug)]
+//!     pub enum Serverbound(ConnectionState) {
+//!         Handshake(HandshakePackets),
+//!         Login(LoginPackets),
+//!         Play(PlayPackets),
+//!     }
+//! }
+//!
+//! let packet = Serverbound::deserialize_in(ConnectionState::Login, &mut reader)?;
+//! ```
+
+use crate::{Reader, Result, Transmittable, Writer};
+
+/// A type with a numeric id, usually implemented with `#[transmittable(packet_id = ...)]`.
+pub trait Packet: Transmittable {
+    const ID: u64;
+}
+
+/// A set of packets dispatched by their ids, implemented by the [`packets!`](crate::packets) macro.
+pub trait PacketSet: Transmittable + Sized {
+    /// The integer type the id is encoded as.
+    type Id: Transmittable + Copy + Into<u64>;
+
+    /// The id of the contained packet.
+    fn id(&self) -> Self::Id;
+
+    /// Returns whether a packet with the id is a part of this set.
+    fn contains_id(id: Self::Id) -> bool;
+
+    /// Reads the packet body for an id which was already read,
+    /// failing with [`Error::UnknownPacketId`](crate::Error::UnknownPacketId) if the id isn't a part of this set.
+    fn deserialize_body<R: Reader>(id: Self::Id, reader: &mut R) -> Result<Self>;
+}
+
+/// A protocol with a packet set per connection state, implemented by the [`protocol!`](crate::protocol) macro.
+pub trait Protocol: Sized {
+    /// The enum of connection states, generated along with the protocol.
+    type State: Copy;
+
+    /// The state the contained packet belongs to.
+    fn state(&self) -> Self::State;
+
+    /// Writes the packet (along with its id), failing with [`Error::InvalidPacketForState`](crate::Error::InvalidPacketForState)
+    /// if it doesn't belong to `state`.
+    fn serialize_in<W: Writer>(&self, state: Self::State, writer: &mut W) -> Result<()>;
+
+    /// Reads a packet valid in `state`, failing with [`Error::InvalidPacketForState`](crate::Error::InvalidPacketForState)
+    /// if its id isn't a part of the state's packet set.
+    fn deserialize_in<R: Reader>(state: Self::State, reader: &mut R) -> Result<Self>;
+}
@@ -0,0 +1,328 @@
+//! Sequences prefixed with a length of a chosen integer type, instead of the `usize` used by the `Vec<T>` and
+//! `String` implementations.
+//!
+//! [`Prefixed<L, T>`] wraps a value and encodes it with an `L` length prefix, e.g. `Prefixed<u16, String>`. Fields of
+//! derived types can use a different prefix without being wrapped with `#[transmittable(len = u16)]`.
+//!
+//! Encoding fails with [`Error::LengthOverflow`] if the length doesn't fit in the prefix.
+//!
+//! The length can also be stored in an earlier field of a derived type, with `#[transmittable(count = "field")]`
+//! holding the number of items (or bytes of a string), and `#[transmittable(size_bytes = "field")]` holding the
+//! encoded byte length. Encoding fails with [`Error::LengthMismatch`] if the field doesn't agree with the value.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use crate::{Error, Reader, Result, Transmittable, Writer};
+
+#[cfg(feature = "tokio")]
+pub use asynchronous::{read_counted_async, read_sized_async, AsyncPrefixable};
+
+/// An integer type lengths can be written as.
+pub trait LengthPrefix: Transmittable + Sized {
+    /// The longest length the prefix can hold.
+    const MAX: usize;
+
+    fn from_len(len: usize) -> Option<Self>;
+
+    /// Converts the prefix back to a length, saturating if it doesn't fit in an `usize`.
+    fn to_len(self) -> usize;
+}
+
+macro_rules! impl_length_prefix {
+    ($($ty:ty),*) => {$(
+        impl LengthPrefix for $ty {
+            const MAX: usize = if (<$ty>::MAX as u128) < usize::MAX as u128 { <$ty>::MAX as usize } else { usize::MAX };
+
+            fn from_len(len: usize) -> Option<Self> {
+                len.try_into().ok()
+            }
+
+            fn to_len(self) -> usize {
+                self.try_into().unwrap_or(usize::MAX)
+            }
+        }
+    )*};
+}
+
+impl_length_prefix!(u8, u16, u32, u64, usize);
+
+/// A value made of a length followed by its contents, whose prefix [`Prefixed`] can replace.
+pub trait Prefixable: Sized {
+    /// The length written in the prefix.
+    fn prefix_len(&self) -> usize;
+
+    /// Writes everything but the length prefix.
+    fn serialize_contents<W: Writer>(&self, writer: &mut W) -> Result<()>;
+
+    /// Reads everything following a length prefix of `len`.
+    fn deserialize_contents<R: Reader>(len: usize, reader: &mut R) -> Result<Self>;
+
+    /// Reads contents taking up all of `bytes`.
+    fn deserialize_bytes(bytes: &[u8]) -> Result<Self>;
+
+    /// The number of bytes `serialize_contents` would write.
+    fn contents_len(&self) -> usize;
+}
+
+impl<T: Transmittable> Prefixable for Vec<T> {
+    fn prefix_len(&self) -> usize {
+        self.len()
+    }
+
+    fn serialize_contents<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        for item in self {
+            Transmittable::serialize(item, writer)?;
+        }
+
+        Ok(())
+    }
+
+    fn deserialize_contents<R: Reader>(len: usize, reader: &mut R) -> Result<Self> {
+        let mut buffer = Vec::with_capacity(len);
+
+        for _ in 0..len {
+            let item = Transmittable::deserialize(reader)?;
+            buffer.push(item);
+        }
+
+        Ok(buffer)
+    }
+
+    fn deserialize_bytes(mut bytes: &[u8]) -> Result<Self> {
+        let mut buffer = Vec::new();
+
+        while !bytes.is_empty() {
+            let remaining = bytes.len();
+            buffer.push(T::deserialize(&mut bytes)?);
+
+            // items without any bytes would never use up the rest
+            if bytes.len() == remaining {
+                return Err(Error::TrailingBytes(remaining));
+            }
+        }
+
+        Ok(buffer)
+    }
+
+    fn contents_len(&self) -> usize {
+        match T::FIXED_SIZE {
+            Some(size) => size * self.len(),
+            None => self.iter().map(Transmittable::encoded_len).sum(),
+        }
+    }
+}
+
+// The prefix holds the length in bytes, just like the `String` implementation
+impl Prefixable for String {
+    fn prefix_len(&self) -> usize {
+        self.len()
+    }
+
+    fn serialize_contents<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(self.as_bytes())?;
+        Ok(())
+    }
+
+    fn deserialize_contents<R: Reader>(len: usize, reader: &mut R) -> Result<Self> {
+        let mut buf = alloc::vec![0u8; len];
+        reader.read_exact(&mut buf)?;
+        Ok(String::from_utf8(buf)?)
+    }
+
+    fn deserialize_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+
+    fn contents_len(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Reads a value whose number of items is stored in another field.
+pub fn read_counted<C: LengthPrefix + Copy, T: Prefixable, R: Reader>(count: &C, reader: &mut R) -> Result<T> {
+    T::deserialize_contents(count.to_len(), reader)
+}
+
+/// Reads a value whose byte length is stored in another field, failing if it doesn't use up all of the bytes.
+pub fn read_sized<C: LengthPrefix + Copy, T: Prefixable, R: Reader>(size: &C, reader: &mut R) -> Result<T> {
+    let mut buf = alloc::vec![0u8; size.to_len()];
+    reader.read_exact(&mut buf)?;
+    T::deserialize_bytes(&buf)
+}
+
+/// Checks the number of items of a value agrees with the field storing it.
+pub fn check_count<C: LengthPrefix + Copy, T: Prefixable>(count: &C, value: &T) -> Result<()> {
+    match (count.to_len(), value.prefix_len()) {
+        (expected, actual) if expected != actual => Err(Error::LengthMismatch { expected, actual }),
+        _ => Ok(()),
+    }
+}
+
+/// Checks the byte length of a value agrees with the field storing it.
+pub fn check_size<C: LengthPrefix + Copy, T: Prefixable>(size: &C, value: &T) -> Result<()> {
+    match (size.to_len(), value.contents_len()) {
+        (expected, actual) if expected != actual => Err(Error::LengthMismatch { expected, actual }),
+        _ => Ok(()),
+    }
+}
+
+/// A value encoded with an `L` length prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Prefixed<L, T> {
+    value: T,
+    _marker: PhantomData<L>,
+}
+
+impl<L, T> Prefixed<L, T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<L: LengthPrefix, T: Prefixable> Prefixed<L, T> {
+    /// Serializes a value which isn't wrapped with an `L` length prefix.
+    pub fn serialize_inner<W: Writer>(value: &T, writer: &mut W) -> Result<()> {
+        let len = value.prefix_len();
+        let prefix = L::from_len(len).ok_or(Error::LengthOverflow { len, max: L::MAX })?;
+
+        prefix.serialize(writer)?;
+        value.serialize_contents(writer)
+    }
+
+    /// The number of bytes [`serialize_inner`](Self::serialize_inner) writes, the prefix is counted as empty if the
+    /// length doesn't fit in it.
+    pub fn encoded_len_inner(value: &T) -> usize {
+        let prefix_len = L::from_len(value.prefix_len()).map_or(0, |prefix| prefix.encoded_len());
+        prefix_len + value.contents_len()
+    }
+}
+
+impl<L, T> From<T> for Prefixed<L, T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<L, T> Deref for Prefixed<L, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<L, T> DerefMut for Prefixed<L, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<L: LengthPrefix, T: Prefixable> Transmittable for Prefixed<L, T> {
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Self::serialize_inner(&self.value, writer)
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let len = L::deserialize(reader)?.to_len();
+        T::deserialize_contents(len, reader).map(Self::new)
+    }
+
+    fn encoded_len(&self) -> usize {
+        Self::encoded_len_inner(&self.value)
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod asynchronous {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::future::Future;
+    use tokio::io::AsyncReadExt;
+    use crate::asynchronous::{AsyncRead, AsyncTransmittable};
+    use crate::Result;
+    use super::{LengthPrefix, Prefixable, Prefixed};
+
+    /// A [`Prefixable`] whose contents can be read asynchronously.
+    pub trait AsyncPrefixable: Prefixable + Send {
+        /// Reads everything following a length prefix of `len`, awaiting until enough bytes are available.
+        fn deserialize_contents_async<R: AsyncRead + Unpin + Send>(len: usize, reader: &mut R) -> impl Future<Output = Result<Self>> + Send;
+    }
+
+    impl<T: AsyncTransmittable> AsyncPrefixable for Vec<T> {
+        async fn deserialize_contents_async<R: AsyncRead + Unpin + Send>(len: usize, reader: &mut R) -> Result<Self> {
+            let mut buffer = Vec::with_capacity(len);
+
+            for _ in 0..len {
+                buffer.push(T::deserialize_async(reader).await?);
+            }
+
+            Ok(buffer)
+        }
+    }
+
+    impl AsyncPrefixable for String {
+        async fn deserialize_contents_async<R: AsyncRead + Unpin + Send>(len: usize, reader: &mut R) -> Result<Self> {
+            let mut buf = alloc::vec![0u8; len];
+            reader.read_exact(&mut buf).await?;
+            Ok(String::from_utf8(buf)?)
+        }
+    }
+
+    /// Reads a value whose number of items is stored in another field asynchronously.
+    pub async fn read_counted_async<C, T, R>(count: &C, reader: &mut R) -> Result<T>
+    where
+        C: LengthPrefix + Copy + Sync,
+        T: AsyncPrefixable,
+        R: AsyncRead + Unpin + Send,
+    {
+        T::deserialize_contents_async(count.to_len(), reader).await
+    }
+
+    /// Reads a value whose byte length is stored in another field asynchronously, failing if it doesn't use up all of
+    /// the bytes.
+    pub async fn read_sized_async<C, T, R>(size: &C, reader: &mut R) -> Result<T>
+    where
+        C: LengthPrefix + Copy + Sync,
+        T: Prefixable,
+        R: AsyncRead + Unpin + Send,
+    {
+        let mut buf = alloc::vec![0u8; size.to_len()];
+        reader.read_exact(&mut buf).await?;
+        T::deserialize_bytes(&buf)
+    }
+
+    impl<L, T> AsyncTransmittable for Prefixed<L, Vec<T>>
+    where
+        L: LengthPrefix + AsyncTransmittable,
+        T: AsyncTransmittable,
+    {
+        async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+            let size = L::deserialize_async(reader).await?.to_len();
+            let mut buffer = Vec::with_capacity(size);
+
+            for _ in 0..size {
+                buffer.push(T::deserialize_async(reader).await?);
+            }
+
+            Ok(Self::new(buffer))
+        }
+    }
+
+    impl<L: LengthPrefix + AsyncTransmittable> AsyncTransmittable for Prefixed<L, String> {
+        async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+            let size = L::deserialize_async(reader).await?.to_len();
+            let mut buf = alloc::vec![0u8; size];
+            reader.read_exact(&mut buf).await?;
+            Ok(Self::new(String::from_utf8(buf)?))
+        }
+    }
+}
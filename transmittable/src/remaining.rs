@@ -0,0 +1,108 @@
+//! Trailing payloads without a length prefix, which run to the end of the input.
+//!
+//! [`Remaining<T>`] is decoded by reading until the reader is exhausted, so it only makes sense as the last value of
+//! a frame, a delimited struct or a slice. Fields of derived types can be read this way without being wrapped with
+//! `#[transmittable(rest)]`, which is only allowed on the last field.
+//!
+//! Nothing tells a truncated value apart from a complete one, so decoding out of a buffer holding only part of the
+//! input succeeds with whatever was received so far. Values ending with a `Remaining` have to be decoded out of
+//! complete frames, and can't go through an [`IncrementalDecoder`](crate::IncrementalDecoder).
+
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+use crate::strings::Text;
+use crate::{Reader, Result, Transmittable, Writer};
+
+/// The raw bytes left in the input.
+pub type Rest = Remaining<Vec<u8>>;
+
+/// A value taking up the rest of the input.
+///
+/// Only decode it once the whole input is there: it's never incomplete, so it's read successfully out of a partial one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Remaining<T = Vec<u8>>(pub T);
+
+impl<T> Remaining<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Text> Remaining<T> {
+    /// Serializes a value which isn't wrapped.
+    pub fn serialize_inner<W: Writer>(value: &T, writer: &mut W) -> Result<()> {
+        writer.write_all(value.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn encoded_len_inner(value: &T) -> usize {
+        value.as_bytes().len()
+    }
+}
+
+impl<T> Deref for Remaining<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Remaining<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Text> Transmittable for Remaining<T> {
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Self::serialize_inner(&self.0, writer)
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let mut buf = Vec::new();
+        read_to_end(reader, &mut buf)?;
+        T::from_bytes(buf).map(Self)
+    }
+
+    fn encoded_len(&self) -> usize {
+        Self::encoded_len_inner(&self.0)
+    }
+}
+
+// `std` readers retry reads which were interrupted
+#[cfg(feature = "std")]
+fn read_to_end<R: Reader>(reader: &mut R, buf: &mut Vec<u8>) -> Result<()> {
+    reader.read_to_end(buf)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "std"))]
+fn read_to_end<R: Reader>(reader: &mut R, buf: &mut Vec<u8>) -> Result<()> {
+    let mut chunk = [0u8; 256];
+
+    loop {
+        match reader.read(&mut chunk)? {
+            0 => return Ok(()),
+            read => buf.extend_from_slice(&chunk[..read]),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod asynchronous {
+    use alloc::vec::Vec;
+    use tokio::io::AsyncReadExt;
+    use crate::asynchronous::{AsyncRead, AsyncTransmittable};
+    use crate::strings::Text;
+    use crate::Result;
+    use super::Remaining;
+
+    impl<T: Text + Send + Sync> AsyncTransmittable for Remaining<T> {
+        async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+            let mut buf = Vec::new();
+            reader.read_to_end(&mut buf).await?;
+            T::from_bytes(buf).map(Self)
+        }
+    }
+}
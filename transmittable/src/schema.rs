@@ -0,0 +1,457 @@
+//! Runtime descriptions of the wire format, enabled by the `schema` feature.
+//!
+//! Every type in this crate implementing [`Transmittable`](crate::Transmittable) implements [`Schema`] as well.
+//! Derived types and `packets!` opt in with `#[transmittable(schema)]`, which requires every field to implement
+//! [`Schema`] and does nothing while the feature is disabled. The resulting [`Layout`]
+//! describes exactly how a value of the type is laid out on the wire, which is useful for generating documentation
+//! or building tooling without having to parse Rust source.
+//!
+//! Recursive types aren't supported, as their layout would be infinitely large.
+
+mod compat;
+mod export;
+
+pub use compat::{assert_wire_compatible, breaking_changes, BreakingChange, Change};
+pub use export::{Json, Markdown};
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::{Bound, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+
+/// A type with a statically known wire layout.
+pub trait Schema {
+    fn schema() -> Layout;
+}
+
+/// The byte order of a multi-byte value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+/// An integer of a given width and signedness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Integer {
+    pub signed: bool,
+    pub bits: u32,
+    pub endian: Endian,
+}
+
+impl Integer {
+    /// A big endian integer, like every integer this crate encodes.
+    pub const fn new(signed: bool, bits: u32) -> Self {
+        Self { signed, bits, endian: Endian::Big }
+    }
+
+    /// The size of the integer in bytes.
+    pub const fn size(&self) -> usize {
+        self.bits as usize / 8
+    }
+}
+
+/// How the length of a string or a sequence is determined.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Length {
+    /// The length is written before the elements as an integer.
+    Prefixed(Integer),
+    /// There are always `count` elements, the count is still written before them as `prefix`.
+    Fixed { count: usize, prefix: Integer },
+    /// The elements are followed by a terminator byte, which they can't contain.
+    Terminated(u8),
+    /// The elements always take up `size` bytes, with the unused ones at the end filled with `padding`.
+    Padded { size: usize, padding: u8 },
+    /// The elements run to the end of the input.
+    Remaining,
+    /// The number of elements is stored in the named field.
+    CountField(String),
+    /// The byte length of the elements is stored in the named field.
+    SizeField(String),
+}
+
+impl Length {
+    /// The number of bytes taken up by the length itself.
+    pub fn size(&self) -> usize {
+        match self {
+            Length::Prefixed(integer) | Length::Fixed { prefix: integer, .. } => integer.size(),
+            Length::Terminated(_) => 1,
+            Length::Padded { .. } | Length::Remaining | Length::CountField(_) | Length::SizeField(_) => 0,
+        }
+    }
+}
+
+/// The layout of a value on the wire.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Layout {
+    /// A single byte, either `0` or `1`.
+    Bool,
+    Integer(Integer),
+    /// An IEEE 754 floating point number.
+    Float { bits: u32, endian: Endian },
+    /// UTF-8 encoded text.
+    String { length: Length },
+    /// A sequence of elements with the same layout.
+    Sequence { length: Length, element: Box<Layout> },
+    /// A presence byte (`0` or `1`), followed by the value if it's present.
+    Option(Box<Layout>),
+    /// Fields laid out one after another, in declaration order.
+    Struct(StructLayout),
+    /// An integer tag followed by the fields of the variant with that tag.
+    Enum(EnumLayout),
+    /// A value only present when the condition over earlier fields holds, without a presence byte.
+    Conditional { condition: String, layout: Box<Layout> },
+    /// A value encoded through serde, named after its Rust type. Its layout follows the serde data model and isn't
+    /// described any further.
+    Opaque(String),
+}
+
+impl Layout {
+    /// The encoded size in bytes, if every value of the layout has the same size.
+    pub fn size(&self) -> Option<usize> {
+        match self {
+            Layout::Bool => Some(1),
+            Layout::Integer(integer) => Some(integer.size()),
+            Layout::Float { bits, .. } => Some(*bits as usize / 8),
+            Layout::String { length: Length::Padded { size, .. } } => Some(*size),
+            Layout::Sequence { length: length @ Length::Fixed { count, .. }, element } => {
+                element.size().map(|size| length.size() + count * size)
+            },
+            Layout::String { .. } | Layout::Sequence { .. } | Layout::Option(_) | Layout::Conditional { .. } | Layout::Opaque(_) => None,
+            Layout::Struct(layout) => fields_size(&layout.fields).map(|size| layout.header_size() + size),
+            Layout::Enum(layout) => {
+                // the catch-all keeps the rest of a delimited variant, so its size is only known without delimiting
+                let catch_all = layout.catch_all.as_ref().map(|_| layout.length.is_none().then_some(0));
+                let mut sizes = layout.variants.iter().map(|variant| fields_size(&variant.fields)).chain(catch_all);
+                let first = sizes.next().unwrap_or(Some(0))?;
+
+                sizes.all(|size| size == Some(first)).then(|| layout.header_size() + first)
+            },
+        }
+    }
+
+    /// Replaces how the length of a string or a sequence is determined, other layouts are returned unchanged.
+    pub fn with_length(self, length: Length) -> Layout {
+        match self {
+            Layout::String { .. } => Layout::String { length },
+            Layout::Sequence { element, .. } => Layout::Sequence { length, element },
+            layout => layout,
+        }
+    }
+
+    /// Makes the layout only present when `condition` holds, like conditional fields of derived types.
+    pub fn conditional(self, condition: &str) -> Layout {
+        Layout::Conditional { condition: condition.into(), layout: Box::new(self) }
+    }
+
+    /// Displays the layout as JSON, see [`Json`].
+    pub fn json(&self) -> Json<'_> {
+        Json(self)
+    }
+
+    /// Displays the layout as a Markdown specification, see [`Markdown`].
+    pub fn markdown(&self) -> Markdown<'_> {
+        Markdown(self)
+    }
+}
+
+fn fields_size(fields: &[FieldLayout]) -> Option<usize> {
+    fields.iter().map(|field| field.layout.size()).sum()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StructLayout {
+    pub name: String,
+    /// For delimited structs, how the byte length of the fields is written in front of them.
+    pub length: Option<Length>,
+    pub fields: Vec<FieldLayout>,
+}
+
+/// A single field, tuple fields are named after their index.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FieldLayout {
+    pub name: String,
+    pub layout: Layout,
+    /// For fields of versioned structs added in a later version, the version peers need to speak to receive them.
+    pub since: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EnumLayout {
+    pub name: String,
+    pub tag: Integer,
+    /// For delimited enums, how the byte length of the fields following the tag is written.
+    pub length: Option<Length>,
+    pub variants: Vec<VariantLayout>,
+    /// The variant unknown tags are decoded as, if any.
+    pub catch_all: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VariantLayout {
+    pub name: String,
+    pub tag: i128,
+    pub fields: Vec<FieldLayout>,
+}
+
+impl StructLayout {
+    pub fn new(name: &str, fields: impl IntoIterator<Item = FieldLayout>) -> Self {
+        Self {
+            name: name.into(),
+            length: None,
+            fields: fields.into_iter().collect(),
+        }
+    }
+
+    /// Prefixes the fields with their byte length.
+    pub fn delimited(self, length: Length) -> Self {
+        Self { length: Some(length), ..self }
+    }
+
+    /// The size of the length prefix in front of the fields.
+    pub fn header_size(&self) -> usize {
+        self.length.as_ref().map_or(0, Length::size)
+    }
+}
+
+impl FieldLayout {
+    pub fn new(name: &str, layout: Layout) -> Self {
+        Self {
+            name: name.into(),
+            layout,
+            since: None,
+        }
+    }
+
+    /// Marks the field as only encoded for peers speaking `version` or newer.
+    pub fn since(self, version: u32) -> Self {
+        Self { since: Some(version), ..self }
+    }
+}
+
+impl EnumLayout {
+    pub fn new(name: &str, tag: Integer, variants: impl IntoIterator<Item = VariantLayout>) -> Self {
+        Self {
+            name: name.into(),
+            tag,
+            length: None,
+            variants: variants.into_iter().collect(),
+            catch_all: None,
+        }
+    }
+
+    /// Prefixes the fields of every variant with their byte length.
+    pub fn delimited(self, length: Length) -> Self {
+        Self { length: Some(length), ..self }
+    }
+
+    /// Decodes unknown tags as `variant` instead of rejecting them.
+    pub fn catch_all(self, variant: &str) -> Self {
+        Self { catch_all: Some(variant.into()), ..self }
+    }
+
+    /// The size of the tag and the length prefix in front of the fields.
+    pub fn header_size(&self) -> usize {
+        self.tag.size() + self.length.as_ref().map_or(0, Length::size)
+    }
+}
+
+impl VariantLayout {
+    pub fn new(name: &str, tag: i128, fields: impl IntoIterator<Item = FieldLayout>) -> Self {
+        Self {
+            name: name.into(),
+            tag,
+            fields: fields.into_iter().collect(),
+        }
+    }
+}
+
+// `usize` and `isize` are described with the width of the platform generating the schema
+macro_rules! impl_integer {
+    ($($ty:ty),*) => {$(
+        impl Schema for $ty {
+            fn schema() -> Layout {
+                Layout::Integer(Integer::new(<$ty>::MIN != 0, <$ty>::BITS))
+            }
+        }
+    )*};
+}
+
+impl_integer!(
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize
+);
+
+impl Schema for f32 {
+    fn schema() -> Layout {
+        Layout::Float { bits: 32, endian: Endian::Big }
+    }
+}
+
+impl Schema for f64 {
+    fn schema() -> Layout {
+        Layout::Float { bits: 64, endian: Endian::Big }
+    }
+}
+
+impl Schema for bool {
+    fn schema() -> Layout {
+        Layout::Bool
+    }
+}
+
+fn usize_prefix() -> Length {
+    Length::Prefixed(Integer::new(false, usize::BITS))
+}
+
+// Arrays are prefixed with their length like a `Vec`, even though it's always `N`
+impl<T: Schema, const N: usize> Schema for [T; N] {
+    fn schema() -> Layout {
+        let length = Length::Fixed { count: N, prefix: Integer::new(false, usize::BITS) };
+        Layout::Sequence { length, element: Box::new(T::schema()) }
+    }
+}
+
+impl<T: Schema> Schema for Vec<T> {
+    fn schema() -> Layout {
+        Layout::Sequence { length: usize_prefix(), element: Box::new(T::schema()) }
+    }
+}
+
+impl Schema for String {
+    fn schema() -> Layout {
+        Layout::String { length: usize_prefix() }
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Schema for ::bytes::Bytes {
+    fn schema() -> Layout {
+        <Vec<u8>>::schema()
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Schema for ::bytes::BytesMut {
+    fn schema() -> Layout {
+        <Vec<u8>>::schema()
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Schema for ::uuid::Uuid {
+    fn schema() -> Layout {
+        Layout::Integer(Integer::new(false, 128))
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: ::smallvec::Array> Schema for ::smallvec::SmallVec<A> where A::Item: Schema {
+    fn schema() -> Layout {
+        <Vec<A::Item>>::schema()
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T: Schema, const CAP: usize> Schema for ::arrayvec::ArrayVec<T, CAP> {
+    fn schema() -> Layout {
+        <Vec<T>>::schema()
+    }
+}
+
+// Maps are sequences of key-value pairs
+#[cfg(feature = "indexmap")]
+impl<K: Schema, V: Schema, S> Schema for ::indexmap::IndexMap<K, V, S> {
+    fn schema() -> Layout {
+        let entry = StructLayout::new("Entry", [FieldLayout::new("key", K::schema()), FieldLayout::new("value", V::schema())]);
+        Layout::Sequence { length: usize_prefix(), element: Box::new(Layout::Struct(entry)) }
+    }
+}
+
+// The length prefix of the wrapped layout is replaced with `L`
+impl<L: crate::LengthPrefix + Schema, T: Schema> Schema for crate::Prefixed<L, T> {
+    fn schema() -> Layout {
+        let Layout::Integer(prefix) = L::schema() else {
+            unreachable!("length prefixes are integers");
+        };
+
+        T::schema().with_length(Length::Prefixed(prefix))
+    }
+}
+
+// The length of the wrapped layout is replaced with the NUL terminator
+impl<T: Schema, const MAX: usize> Schema for crate::NulTerminated<T, MAX> {
+    fn schema() -> Layout {
+        T::schema().with_length(Length::Terminated(0))
+    }
+}
+
+impl<T: Schema> Schema for crate::Remaining<T> {
+    fn schema() -> Layout {
+        T::schema().with_length(Length::Remaining)
+    }
+}
+
+impl Schema for alloc::ffi::CString {
+    fn schema() -> Layout {
+        Layout::Sequence { length: Length::Terminated(0), element: Box::new(u8::schema()) }
+    }
+}
+
+impl<const N: usize, const PAD: u8> Schema for crate::FixedStr<N, PAD> {
+    fn schema() -> Layout {
+        Layout::String { length: Length::Padded { size: N, padding: PAD } }
+    }
+}
+
+impl<T: Schema> Schema for Option<T> {
+    fn schema() -> Layout {
+        Layout::Option(Box::new(T::schema()))
+    }
+}
+
+impl<T: Schema> Schema for Range<T> {
+    fn schema() -> Layout {
+        Layout::Struct(StructLayout::new("Range", [FieldLayout::new("start", T::schema()), FieldLayout::new("end", T::schema())]))
+    }
+}
+
+impl<T: Schema> Schema for RangeInclusive<T> {
+    fn schema() -> Layout {
+        Layout::Struct(StructLayout::new("RangeInclusive", [FieldLayout::new("start", T::schema()), FieldLayout::new("end", T::schema())]))
+    }
+}
+
+impl<T: Schema> Schema for RangeFrom<T> {
+    fn schema() -> Layout {
+        Layout::Struct(StructLayout::new("RangeFrom", [FieldLayout::new("start", T::schema())]))
+    }
+}
+
+impl<T: Schema> Schema for RangeTo<T> {
+    fn schema() -> Layout {
+        Layout::Struct(StructLayout::new("RangeTo", [FieldLayout::new("end", T::schema())]))
+    }
+}
+
+impl<T: Schema> Schema for RangeToInclusive<T> {
+    fn schema() -> Layout {
+        Layout::Struct(StructLayout::new("RangeToInclusive", [FieldLayout::new("end", T::schema())]))
+    }
+}
+
+impl Schema for RangeFull {
+    fn schema() -> Layout {
+        Layout::Struct(StructLayout::new("RangeFull", []))
+    }
+}
+
+impl<T: Schema> Schema for Bound<T> {
+    fn schema() -> Layout {
+        Layout::Enum(EnumLayout::new("Bound", Integer::new(false, 8), [
+            VariantLayout::new("Included", 0, [FieldLayout::new("0", T::schema())]),
+            VariantLayout::new("Excluded", 1, [FieldLayout::new("0", T::schema())]),
+            VariantLayout::new("Unbounded", 2, []),
+        ]))
+    }
+}
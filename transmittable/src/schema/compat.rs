@@ -0,0 +1,253 @@
+//! Detection of changes that break wire compatibility between two versions of a layout.
+
+use super::export::write_type_name;
+use super::{FieldLayout, Integer, Layout, Length, Schema};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+/// A change that prevents peers using different versions of a layout from understanding each other.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BreakingChange {
+    /// Where the change happened, like `Shape::Rect.w` or `Message.tags[]`.
+    pub path: String,
+    pub change: Change,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Change {
+    /// The value is encoded in an entirely different way.
+    LayoutChanged { old: Layout, new: Layout },
+    /// Length prefixes were added to or removed from the encoding.
+    DelimitingChanged,
+    /// The width, signedness or byte order of an integer changed.
+    IntegerChanged { old: Integer, new: Integer },
+    /// The length of a string or a sequence is determined in a different way.
+    LengthChanged { old: Length, new: Length },
+    FieldRemoved(String),
+    FieldAdded(String),
+    /// The field is still there, but in a different position, so its values are read into another field.
+    FieldMoved { name: String, old: usize, new: usize },
+    VariantRemoved(String),
+    VariantTagChanged { name: String, old: i128, new: i128 },
+    /// A tag now belongs to a variant which had another tag before, or which the old layout didn't have while the old
+    /// variant is still there, so values of the old variant are read as the new one.
+    TagReused { tag: i128, old: String, new: String },
+    /// Unknown tags were decoded as this variant before, but are rejected now.
+    CatchAllRemoved(String),
+}
+
+impl Display for BreakingChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.path)?;
+
+        match &self.change {
+            Change::LayoutChanged { old, new } => {
+                f.write_str("changed from ")?;
+                write_type_name(f, old)?;
+                f.write_str(" to ")?;
+                write_type_name(f, new)
+            },
+            Change::IntegerChanged { old, new } => {
+                f.write_str("changed from ")?;
+                write_type_name(f, &Layout::Integer(*old))?;
+                f.write_str(" to ")?;
+                write_type_name(f, &Layout::Integer(*new))
+            },
+            Change::LengthChanged { old, new } => {
+                f.write_str("the length changed from ")?;
+                write_length(f, old)?;
+                f.write_str(" to ")?;
+                write_length(f, new)
+            },
+            Change::DelimitingChanged => f.write_str("length prefixes were added or removed"),
+            Change::FieldRemoved(name) => write!(f, "field `{name}` was removed"),
+            Change::FieldAdded(name) => write!(f, "field `{name}` was added"),
+            Change::FieldMoved { name, old, new } => write!(f, "field `{name}` moved from position {old} to {new}"),
+            Change::VariantRemoved(name) => write!(f, "variant `{name}` was removed"),
+            Change::VariantTagChanged { name, old, new } => write!(f, "variant `{name}` changed its tag from {old} to {new}"),
+            Change::TagReused { tag, old, new } => write!(f, "variant `{new}` reuses tag {tag} of variant `{old}`"),
+            Change::CatchAllRemoved(name) => write!(f, "the catch-all variant `{name}` was removed"),
+        }
+    }
+}
+
+fn write_length(f: &mut Formatter<'_>, length: &Length) -> fmt::Result {
+    match length {
+        Length::Prefixed(integer) => {
+            f.write_str("a ")?;
+            write_type_name(f, &Layout::Integer(*integer))?;
+            f.write_str(" prefix")
+        },
+        Length::Fixed { count, prefix } => {
+            write!(f, "{count} items with a ")?;
+            write_type_name(f, &Layout::Integer(*prefix))?;
+            f.write_str(" prefix")
+        },
+        Length::Terminated(terminator) => write!(f, "a {terminator:#04x} terminator"),
+        Length::Padded { size, padding } => write!(f, "{size} bytes padded with {padding:#04x}"),
+        Length::Remaining => f.write_str("the rest of the input"),
+        Length::CountField(field) => write!(f, "the item count in `{field}`"),
+        Length::SizeField(field) => write!(f, "the byte length in `{field}`"),
+    }
+}
+
+/// Compares two versions of a layout and returns every change breaking wire compatibility between them.
+///
+/// Fields are matched by position and variants by tag, just like on the wire, so renaming either isn't breaking.
+/// Names are only used to notice fields moved to another position and tags taken over by another variant.
+/// Adding an enum variant with an unused tag isn't considered breaking, as every value of the old layout can still
+/// be decoded with the new one, but removing the catch-all variant is, since tags unknown to the old layout were
+/// accepted by it. Neither is adding fields anywhere in a versioned struct as long as they're marked with
+/// the version they were added in, since peers speaking older versions never receive them.
+pub fn breaking_changes(old: &Layout, new: &Layout) -> Vec<BreakingChange> {
+    let path = match new {
+        Layout::Struct(layout) => layout.name.clone(),
+        Layout::Enum(layout) => layout.name.clone(),
+        _ => "value".into(),
+    };
+
+    let mut changes = Vec::new();
+    compare(&path, old, new, &mut changes);
+
+    changes
+}
+
+/// Panics with a list of breaking changes if `New` isn't wire compatible with `Old`.
+///
+/// Meant for tests keeping the previous version of a type around, so incompatible changes fail the build.
+#[track_caller]
+pub fn assert_wire_compatible<Old: Schema, New: Schema>() {
+    let changes = breaking_changes(&Old::schema(), &New::schema());
+
+    if !changes.is_empty() {
+        let changes: String = changes.iter().map(|change| format!("\n  - {change}")).collect();
+        panic!("layouts are not wire compatible:{changes}");
+    }
+}
+
+fn push(changes: &mut Vec<BreakingChange>, path: &str, change: Change) {
+    changes.push(BreakingChange { path: path.into(), change });
+}
+
+fn compare_integer(path: &str, old: &Integer, new: &Integer, changes: &mut Vec<BreakingChange>) {
+    if old != new {
+        push(changes, path, Change::IntegerChanged { old: *old, new: *new });
+    }
+}
+
+fn compare_length(path: &str, old: &Length, new: &Length, changes: &mut Vec<BreakingChange>) {
+    match (old, new) {
+        (Length::Prefixed(old), Length::Prefixed(new)) => compare_integer(&format!("{path}.length"), old, new, changes),
+        (old, new) if old == new => {},
+        (old, new) => push(changes, path, Change::LengthChanged { old: old.clone(), new: new.clone() }),
+    }
+}
+
+fn compare(path: &str, old: &Layout, new: &Layout, changes: &mut Vec<BreakingChange>) {
+    match (old, new) {
+        (Layout::Bool, Layout::Bool) => {},
+        (Layout::Integer(old), Layout::Integer(new)) => compare_integer(path, old, new, changes),
+        (Layout::String { length: old }, Layout::String { length: new }) => compare_length(path, old, new, changes),
+        (Layout::Sequence { length: old_length, element: old }, Layout::Sequence { length: new_length, element: new }) => {
+            compare_length(path, old_length, new_length, changes);
+            compare(&format!("{path}[]"), old, new, changes);
+        },
+        (Layout::Option(old), Layout::Option(new)) => compare(&format!("{path}?"), old, new, changes),
+        (Layout::Conditional { condition: old_condition, layout: old }, Layout::Conditional { condition: new_condition, layout: new })
+            if old_condition == new_condition => compare(&format!("{path}?"), old, new, changes),
+        (Layout::Struct(old), Layout::Struct(new)) => {
+            if compare_delimiting(path, old.length.as_ref(), new.length.as_ref(), changes) {
+                compare_fields(path, &old.fields, &new.fields, old.length.is_some(), changes);
+            }
+        },
+        (Layout::Enum(old), Layout::Enum(new)) => {
+            compare_integer(&format!("{path}.tag"), &old.tag, &new.tag, changes);
+
+            // variants of delimited enums tolerate trailing fields being added or removed, just like delimited structs
+            let delimited = compare_delimiting(path, old.length.as_ref(), new.length.as_ref(), changes) && old.length.is_some();
+
+            for variant in &old.variants {
+                let moved = new.variants.iter().find(|new| new.name == variant.name && new.tag != variant.tag);
+                if let Some(moved) = moved {
+                    push(changes, path, Change::VariantTagChanged {
+                        name: variant.name.clone(),
+                        old: variant.tag,
+                        new: moved.tag,
+                    });
+                }
+
+                match new.variants.iter().find(|new| new.tag == variant.tag) {
+                    // a different name is only a rename if neither variant exists elsewhere in the other layout
+                    Some(new_variant) if new_variant.name != variant.name
+                        && (moved.is_some() || old.variants.iter().any(|old| old.name == new_variant.name)) => {
+                        push(changes, path, Change::TagReused {
+                            tag: variant.tag,
+                            old: variant.name.clone(),
+                            new: new_variant.name.clone(),
+                        });
+                    },
+                    Some(new_variant) => {
+                        compare_fields(&format!("{path}::{}", new_variant.name), &variant.fields, &new_variant.fields, delimited, changes);
+                    },
+                    None if moved.is_none() => push(changes, path, Change::VariantRemoved(variant.name.clone())),
+                    None => {},
+                }
+            }
+
+            if let (Some(catch_all), None) = (&old.catch_all, &new.catch_all) {
+                push(changes, path, Change::CatchAllRemoved(catch_all.clone()));
+            }
+        },
+        (old, new) if old == new => {},
+        (old, new) => push(changes, path, Change::LayoutChanged { old: old.clone(), new: new.clone() }),
+    }
+}
+
+// Returns whether both layouts are delimited the same way
+fn compare_delimiting(path: &str, old: Option<&Length>, new: Option<&Length>, changes: &mut Vec<BreakingChange>) -> bool {
+    match (old, new) {
+        (Some(old), Some(new)) => compare_length(path, old, new, changes),
+        (None, None) => {},
+        _ => {
+            push(changes, path, Change::DelimitingChanged);
+            return false;
+        },
+    }
+
+    true
+}
+
+fn compare_fields(path: &str, old: &[FieldLayout], new: &[FieldLayout], delimited: bool, changes: &mut Vec<BreakingChange>) {
+    // Fields added in a later version are left out for peers speaking the old one
+    let new: Vec<&FieldLayout> = new.iter()
+        .filter(|new| new.since.is_none() || old.iter().any(|old| old.name == new.name))
+        .collect();
+
+    // Fields are matched by position, but a field found at another position would have its values read into another
+    for (i, field) in old.iter().enumerate() {
+        match new.iter().position(|new| new.name == field.name) {
+            Some(position) if position != i => push(changes, path, Change::FieldMoved { name: field.name.clone(), old: i, new: position }),
+            _ => {},
+        }
+    }
+
+    for (old_field, new_field) in old.iter().zip(&new) {
+        compare(&format!("{path}.{}", new_field.name), &old_field.layout, &new_field.layout, changes);
+    }
+
+    // Delimited decoders skip unknown trailing fields and default missing ones, so fields can be appended to or
+    // removed from the end
+    if delimited {
+        return;
+    }
+
+    for field in old.iter().skip(new.len()) {
+        push(changes, path, Change::FieldRemoved(field.name.clone()));
+    }
+
+    for field in new.iter().skip(old.len()) {
+        push(changes, path, Change::FieldAdded(field.name.clone()));
+    }
+}
@@ -0,0 +1,394 @@
+//! Exporters turning a [`Layout`] into a specification for implementations in other languages.
+
+use super::{Endian, FieldLayout, Integer, Layout, Length};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter, Write};
+
+/// Displays a [`Layout`] as a JSON document.
+///
+/// Every layout is an object with a `kind` and a `size`, which is `null` for layouts with a variable size.
+/// Fields carry their byte `offset` from the start of the enclosing struct or enum, `null` once a field with
+/// a variable size precedes them.
+/// Delimited structs and enums additionally carry the `length` prefixing their fields, and fields added in a later
+/// version of a versioned struct the version they were added in as `since`. Offsets are those of the newest version.
+/// Enums accepting unknown tags carry the name of the variant decoding them as `catch_all`.
+pub struct Json<'a>(pub &'a Layout);
+
+/// Displays a [`Layout`] as a Markdown specification.
+///
+/// Every struct and enum reachable from the layout gets its own section with a table of offsets, fields,
+/// types and sizes, so the wire format can be implemented without reading the Rust source.
+pub struct Markdown<'a>(pub &'a Layout);
+
+impl Display for Json<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        json_layout(f, self.0)
+    }
+}
+
+fn json_string(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+
+    f.write_char('"')
+}
+
+fn json_option(f: &mut Formatter<'_>, value: Option<usize>) -> fmt::Result {
+    match value {
+        Some(value) => write!(f, "{value}"),
+        None => f.write_str("null"),
+    }
+}
+
+fn json_endian(endian: Endian) -> &'static str {
+    match endian {
+        Endian::Big => "big",
+        Endian::Little => "little",
+    }
+}
+
+fn json_integer(f: &mut Formatter<'_>, integer: &Integer) -> fmt::Result {
+    write!(
+        f,
+        r#"{{"kind":"integer","size":{},"signed":{},"bits":{},"endian":"{}"}}"#,
+        integer.size(), integer.signed, integer.bits, json_endian(integer.endian),
+    )
+}
+
+fn json_length(f: &mut Formatter<'_>, length: &Length) -> fmt::Result {
+    match length {
+        Length::Prefixed(integer) => {
+            f.write_str(r#"{"kind":"prefixed","integer":"#)?;
+            json_integer(f, integer)?;
+            f.write_char('}')
+        },
+        Length::Fixed { count, prefix } => {
+            write!(f, r#"{{"kind":"fixed","count":{count},"integer":"#)?;
+            json_integer(f, prefix)?;
+            f.write_char('}')
+        },
+        Length::Terminated(terminator) => write!(f, r#"{{"kind":"terminated","terminator":{terminator}}}"#),
+        Length::Padded { size, padding } => write!(f, r#"{{"kind":"padded","size":{size},"padding":{padding}}}"#),
+        Length::Remaining => f.write_str(r#"{"kind":"remaining"}"#),
+        Length::CountField(field) => {
+            f.write_str(r#"{"kind":"count_field","field":"#)?;
+            json_string(f, field)?;
+            f.write_char('}')
+        },
+        Length::SizeField(field) => {
+            f.write_str(r#"{"kind":"size_field","field":"#)?;
+            json_string(f, field)?;
+            f.write_char('}')
+        },
+    }
+}
+
+// Only delimited structs and enums have a `length`
+fn json_delimiting(f: &mut Formatter<'_>, length: Option<&Length>) -> fmt::Result {
+    match length {
+        Some(length) => {
+            f.write_str(r#","length":"#)?;
+            json_length(f, length)
+        },
+        None => Ok(()),
+    }
+}
+
+fn json_fields(f: &mut Formatter<'_>, start: usize, fields: &[FieldLayout]) -> fmt::Result {
+    f.write_char('[')?;
+
+    for (i, (field, offset)) in fields.iter().zip(offsets(start, fields)).enumerate() {
+        if i > 0 {
+            f.write_char(',')?;
+        }
+
+        f.write_str(r#"{"name":"#)?;
+        json_string(f, &field.name)?;
+        f.write_str(r#","offset":"#)?;
+        json_option(f, offset)?;
+        f.write_str(r#","layout":"#)?;
+        json_layout(f, &field.layout)?;
+        if let Some(since) = field.since {
+            write!(f, r#","since":{since}"#)?;
+        }
+        f.write_char('}')?;
+    }
+
+    f.write_char(']')
+}
+
+fn json_layout(f: &mut Formatter<'_>, layout: &Layout) -> fmt::Result {
+    let kind = match layout {
+        Layout::Bool => "bool",
+        Layout::Integer(integer) => return json_integer(f, integer),
+        Layout::Float { .. } => "float",
+        Layout::String { .. } => "string",
+        Layout::Sequence { .. } => "sequence",
+        Layout::Option(_) => "option",
+        Layout::Struct(_) => "struct",
+        Layout::Enum(_) => "enum",
+        Layout::Conditional { .. } => "conditional",
+        Layout::Opaque(_) => "opaque",
+    };
+
+    write!(f, r#"{{"kind":"{kind}","size":"#)?;
+    json_option(f, layout.size())?;
+
+    match layout {
+        Layout::Bool | Layout::Integer(_) => {},
+        Layout::Float { bits, endian } => write!(f, r#","bits":{bits},"endian":"{}""#, json_endian(*endian))?,
+        Layout::String { length } => {
+            f.write_str(r#","length":"#)?;
+            json_length(f, length)?;
+        },
+        Layout::Sequence { length, element } => {
+            f.write_str(r#","length":"#)?;
+            json_length(f, length)?;
+            f.write_str(r#","element":"#)?;
+            json_layout(f, element)?;
+        },
+        Layout::Option(value) => {
+            f.write_str(r#","value":"#)?;
+            json_layout(f, value)?;
+        },
+        Layout::Conditional { condition, layout } => {
+            f.write_str(r#","condition":"#)?;
+            json_string(f, condition)?;
+            f.write_str(r#","value":"#)?;
+            json_layout(f, layout)?;
+        },
+        Layout::Opaque(name) => {
+            f.write_str(r#","name":"#)?;
+            json_string(f, name)?;
+        },
+        Layout::Struct(layout) => {
+            f.write_str(r#","name":"#)?;
+            json_string(f, &layout.name)?;
+            json_delimiting(f, layout.length.as_ref())?;
+            f.write_str(r#","fields":"#)?;
+            json_fields(f, layout.header_size(), &layout.fields)?;
+        },
+        Layout::Enum(layout) => {
+            f.write_str(r#","name":"#)?;
+            json_string(f, &layout.name)?;
+            f.write_str(r#","tag":"#)?;
+            json_integer(f, &layout.tag)?;
+            json_delimiting(f, layout.length.as_ref())?;
+            f.write_str(r#","variants":["#)?;
+
+            for (i, variant) in layout.variants.iter().enumerate() {
+                if i > 0 {
+                    f.write_char(',')?;
+                }
+
+                f.write_str(r#"{"name":"#)?;
+                json_string(f, &variant.name)?;
+                write!(f, r#","tag":{},"fields":"#, variant.tag)?;
+                // offsets of variant fields include the tag (and length) in front of them
+                json_fields(f, layout.header_size(), &variant.fields)?;
+                f.write_char('}')?;
+            }
+
+            f.write_char(']')?;
+
+            if let Some(catch_all) = &layout.catch_all {
+                f.write_str(r#","catch_all":"#)?;
+                json_string(f, catch_all)?;
+            }
+        },
+    }
+
+    f.write_char('}')
+}
+
+// The offset of every field, known until the first field with a variable size
+fn offsets(start: usize, fields: &[FieldLayout]) -> impl Iterator<Item = Option<usize>> + '_ {
+    fields.iter().scan(Some(start), |offset, field| {
+        let current = *offset;
+        *offset = offset.zip(field.layout.size()).map(|(offset, size)| offset + size);
+
+        Some(current)
+    })
+}
+
+impl Display for Markdown<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Integers and floats are big endian unless noted otherwise, ")?;
+        f.write_str("booleans and option presence bytes are encoded as `0` or `1`.\n")?;
+
+        let mut seen = Vec::new();
+        markdown_sections(f, self.0, &mut seen)
+    }
+}
+
+pub(super) fn write_type_name(f: &mut impl Write, layout: &Layout) -> fmt::Result {
+    fn endian(f: &mut impl Write, endian: Endian) -> fmt::Result {
+        match endian {
+            Endian::Big => Ok(()),
+            Endian::Little => f.write_str(" (little endian)"),
+        }
+    }
+
+    fn integer(f: &mut impl Write, integer: &Integer) -> fmt::Result {
+        write!(f, "{}{}", if integer.signed { 'i' } else { 'u' }, integer.bits)?;
+        endian(f, integer.endian)
+    }
+
+    fn length(f: &mut impl Write, length: &Length) -> fmt::Result {
+        match length {
+            Length::Prefixed(length) => {
+                f.write_str(", ")?;
+                integer(f, length)?;
+                f.write_str(" length prefix")
+            },
+            Length::Fixed { count, prefix } => {
+                write!(f, ", {count} items with a ")?;
+                integer(f, prefix)?;
+                f.write_str(" length prefix")
+            },
+            Length::Terminated(terminator) => write!(f, ", terminated by {terminator:#04x}"),
+            Length::Padded { size, padding } => write!(f, ", {size} bytes padded with {padding:#04x}"),
+            Length::Remaining => f.write_str(", up to the end of the input"),
+            Length::CountField(field) => write!(f, ", `{field}` items long"),
+            Length::SizeField(field) => write!(f, ", `{field}` bytes long"),
+        }
+    }
+
+    match layout {
+        Layout::Bool => f.write_str("bool"),
+        Layout::Integer(layout) => integer(f, layout),
+        Layout::Float { bits, endian: order } => {
+            write!(f, "f{bits}")?;
+            endian(f, *order)
+        },
+        Layout::String { length: layout } => {
+            f.write_str("UTF-8 string")?;
+            length(f, layout)
+        },
+        Layout::Sequence { length: layout, element } => {
+            f.write_str("sequence of ")?;
+            write_type_name(f, element)?;
+            length(f, layout)
+        },
+        Layout::Option(value) => {
+            f.write_str("optional ")?;
+            write_type_name(f, value)
+        },
+        Layout::Struct(layout) => write!(f, "`{}`", layout.name),
+        Layout::Enum(layout) => write!(f, "`{}`", layout.name),
+        // pipes would end the table cell
+        Layout::Conditional { condition, layout } => {
+            write_type_name(f, layout)?;
+            write!(f, " if `{}`", condition.replace('|', "\\|"))
+        },
+        Layout::Opaque(name) => write!(f, "`{name}` encoded through serde"),
+    }
+}
+
+fn markdown_size(f: &mut Formatter<'_>, size: Option<usize>) -> fmt::Result {
+    match size {
+        Some(size) => write!(f, "{size}"),
+        None => f.write_str("variable"),
+    }
+}
+
+fn markdown_fields(f: &mut Formatter<'_>, start: usize, fields: &[FieldLayout]) -> fmt::Result {
+    f.write_str("\n| Offset | Field | Type | Size |\n| --- | --- | --- | --- |\n")?;
+
+    for (field, offset) in fields.iter().zip(offsets(start, fields)) {
+        f.write_str("| ")?;
+        markdown_size(f, offset)?;
+        write!(f, " | {}", field.name)?;
+        if let Some(since) = field.since {
+            write!(f, " (since version {since})")?;
+        }
+        f.write_str(" | ")?;
+        write_type_name(f, &field.layout)?;
+        f.write_str(" | ")?;
+        markdown_size(f, field.layout.size())?;
+        f.write_str(" |\n")?;
+    }
+
+    Ok(())
+}
+
+// Sections are written depth first, every distinct struct and enum only once
+fn markdown_sections<'a>(f: &mut Formatter<'_>, layout: &'a Layout, seen: &mut Vec<&'a Layout>) -> fmt::Result {
+    let fields: Vec<&FieldLayout> = match layout {
+        Layout::Bool | Layout::Integer(_) | Layout::Float { .. } | Layout::String { .. } | Layout::Opaque(_) => return Ok(()),
+        Layout::Sequence { element: layout, .. } | Layout::Option(layout) | Layout::Conditional { layout, .. } => return markdown_sections(f, layout, seen),
+        Layout::Struct(_) | Layout::Enum(_) if seen.contains(&layout) => return Ok(()),
+        Layout::Struct(struct_layout) => {
+            seen.push(layout);
+
+            write!(f, "\n## `{}`\n\nSize: ", struct_layout.name)?;
+            markdown_size(f, layout.size())?;
+            f.write_char('\n')?;
+
+            if let Some(Length::Prefixed(length)) = &struct_layout.length {
+                f.write_str("\nPrefixed with the byte length of the fields as ")?;
+                write_type_name(f, &Layout::Integer(*length))?;
+                f.write_str(". Field offsets include the length, decoders skip bytes beyond the known fields ")?;
+                f.write_str("and use defaults for fields missing from the end.\n")?;
+            }
+
+            if struct_layout.fields.is_empty() {
+                f.write_str("\nNo fields.\n")?;
+            } else {
+                markdown_fields(f, struct_layout.header_size(), &struct_layout.fields)?;
+            }
+
+            struct_layout.fields.iter().collect()
+        },
+        Layout::Enum(enum_layout) => {
+            seen.push(layout);
+
+            write!(f, "\n## `{}`\n\nSize: ", enum_layout.name)?;
+            markdown_size(f, layout.size())?;
+            write!(f, "\n\nTag: ")?;
+            write_type_name(f, &Layout::Integer(enum_layout.tag))?;
+
+            match &enum_layout.length {
+                Some(Length::Prefixed(length)) => {
+                    f.write_str(", followed by the byte length of the fields as ")?;
+                    write_type_name(f, &Layout::Integer(*length))?;
+                    f.write_str(". Field offsets include the tag and the length, ")?;
+                    f.write_str("decoders skip bytes beyond the known fields ")?;
+                    f.write_str("and use defaults for fields missing from the end.\n")?;
+                },
+                // enums are only ever delimited by a length prefix
+                _ => f.write_str(", field offsets include the tag.\n")?,
+            }
+
+            if let Some(catch_all) = &enum_layout.catch_all {
+                write!(f, "\nUnknown tags are accepted and decoded as `{}::{catch_all}`.\n", enum_layout.name)?;
+            }
+
+            for variant in &enum_layout.variants {
+                write!(f, "\n### `{}::{}` (tag `{}`)\n", enum_layout.name, variant.name, variant.tag)?;
+
+                if variant.fields.is_empty() {
+                    f.write_str("\nNo fields.\n")?;
+                } else {
+                    markdown_fields(f, enum_layout.header_size(), &variant.fields)?;
+                }
+            }
+
+            enum_layout.variants.iter().flat_map(|variant| &variant.fields).collect()
+        },
+    };
+
+    for field in fields {
+        markdown_sections(f, &field.layout, seen)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+//! Streaming access to sequences without collecting them into a [`Vec`](alloc::vec::Vec).
+//!
+//! Both types use the exact wire format of `Vec<T>`: an `usize` length prefix followed by the items.
+
+use core::marker::PhantomData;
+use crate::{Error, Reader, Result, Transmittable, Writer};
+
+/// Reads the length prefix of a sequence and then lazily yields its items.
+///
+/// The iterator is fused after the first error, as the position in the stream is unknown at that point.
+#[derive(Debug)]
+pub struct SeqReader<R, T> {
+    reader: R,
+    remaining: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: Reader, T: Transmittable> SeqReader<R, T> {
+    /// Reads the length prefix, leaving the items in the reader.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let remaining = usize::deserialize(&mut reader)?;
+
+        Ok(Self {
+            reader,
+            remaining,
+            _marker: PhantomData,
+        })
+    }
+
+    /// The amount of items which weren't read yet.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Reader, T: Transmittable> Iterator for SeqReader<R, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let item = T::deserialize(&mut self.reader);
+        self.remaining = if item.is_ok() { self.remaining - 1 } else { 0 };
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+/// Writes a sequence of a known length item by item.
+///
+/// Writing more items than announced, or finishing before all of them were written,
+/// fails with [`Error::LengthMismatch`].
+#[derive(Debug)]
+pub struct SeqWriter<W> {
+    writer: W,
+    len: usize,
+    written: usize,
+}
+
+impl<W: Writer> SeqWriter<W> {
+    /// Writes the length prefix for a sequence of `len` items.
+    pub fn new(mut writer: W, len: usize) -> Result<Self> {
+        len.serialize(&mut writer)?;
+
+        Ok(Self {
+            writer,
+            len,
+            written: 0,
+        })
+    }
+
+    pub fn write<T: Transmittable>(&mut self, item: &T) -> Result<()> {
+        if self.written == self.len {
+            return Err(Error::LengthMismatch { expected: self.len, actual: self.len + 1 });
+        }
+
+        item.serialize(&mut self.writer)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Checks that every announced item was written and returns the underlying writer.
+    pub fn finish(self) -> Result<W> {
+        if self.written != self.len {
+            return Err(Error::LengthMismatch { expected: self.len, actual: self.written });
+        }
+
+        Ok(self.writer)
+    }
+}
+
+/// Writes the items from an iterator with a known length, in the same format as `Vec<T>`.
+pub fn write_seq<'a, W, T, I>(writer: &mut W, items: I) -> Result<()>
+where
+    W: Writer,
+    T: Transmittable + 'a,
+    I: IntoIterator<Item = &'a T>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let items = items.into_iter();
+    let mut seq = SeqWriter::new(writer, items.len())?;
+
+    for item in items {
+        seq.write(item)?;
+    }
+
+    seq.finish()?;
+    Ok(())
+}
@@ -0,0 +1,490 @@
+//! A serde [`Serializer`] and [`Deserializer`] for the wire format, enabled by the `serde` feature.
+//!
+//! Values are encoded exactly like the [`Transmittable`] implementations in this crate and the derive macro:
+//! integers and floats are big endian, booleans and option presence bytes are `0` or `1`, strings, byte buffers,
+//! sequences and maps are prefixed with their length as an `usize`, and struct fields are written one after another.
+//! Enum variants are tagged with their index, written as configured by [`Config`] (a `u32` by default). Tuples are
+//! prefixed with their length like arrays, and `char`s are written as their `u32` code point.
+//!
+//! The derive macro tags enums with their discriminant written as their `repr` instead, so a type deriving both
+//! `Serialize` and `Transmittable` only encodes to the same bytes through both when its discriminants are equal to
+//! the variant indices (as they are without explicit discriminants) and the [`TagWidth`] matches the `repr`.
+//!
+//! The format isn't self-describing, so `deserialize_any` and skipping values of unknown types aren't supported.
+//! Single fields of derived types can be routed through serde with `#[transmittable(serde)]`. Their encoded length
+//! isn't known before reading them, so they can't be decoded asynchronously and deriving `AsyncTransmittable` with
+//! `#[transmittable(async)]` fails on the attribute. Such types can still be decoded synchronously out of a frame
+//! read asynchronously, like the ones decoded by `TransmittableCodec`.
+
+use ::serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use ::serde::ser::{self, Serialize};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
+use crate::size::SizeCounter;
+use crate::{Error, Reader, Result, Transmittable, Writer};
+
+/// How the variant index of enums is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TagWidth {
+    U8,
+    U16,
+    #[default]
+    U32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Config {
+    pub tag: TagWidth,
+}
+
+impl Config {
+    pub const fn new(tag: TagWidth) -> Self {
+        Self { tag }
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Serde(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Serde(msg.to_string())
+    }
+}
+
+/// Serializes the value through serde.
+pub fn serialize<T: Serialize + ?Sized, W: Writer>(value: &T, writer: &mut W) -> Result<()> {
+    value.serialize(&mut Serializer::new(writer))
+}
+
+/// Deserializes a value through serde.
+pub fn deserialize<T: DeserializeOwned, R: Reader>(reader: &mut R) -> Result<T> {
+    T::deserialize(&mut Deserializer::new(reader))
+}
+
+/// The amount of bytes [`serialize`] writes for the value.
+pub fn encoded_len<T: Serialize + ?Sized>(value: &T) -> usize {
+    let mut counter = SizeCounter::default();
+    // values failing to serialize fail again once they're serialized for real
+    let _ = serialize(value, &mut counter);
+
+    counter.count()
+}
+
+pub struct Serializer<W> {
+    writer: W,
+    config: Config,
+}
+
+impl<W: Writer> Serializer<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_config(writer, Config::default())
+    }
+
+    pub fn with_config(writer: W, config: Config) -> Self {
+        Self { writer, config }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write<T: Transmittable>(&mut self, value: T) -> Result<()> {
+        value.serialize(&mut self.writer)
+    }
+
+    fn write_tag(&mut self, index: u32) -> Result<()> {
+        let overflow = |_| Error::Serde(format!("variant index {index} doesn't fit in the tag"));
+
+        match self.config.tag {
+            TagWidth::U8 => self.write(u8::try_from(index).map_err(overflow)?),
+            TagWidth::U16 => self.write(u16::try_from(index).map_err(overflow)?),
+            TagWidth::U32 => self.write(index),
+        }
+    }
+}
+
+macro_rules! serialize_primitives {
+    ($($method:ident: $ty:ty),*) => {$(
+        fn $method(self, v: $ty) -> Result<()> {
+            self.write(v)
+        }
+    )*};
+}
+
+impl<W: Writer> ser::Serializer for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    serialize_primitives!(
+        serialize_bool: bool,
+        serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64, serialize_i128: i128,
+        serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64, serialize_u128: u128,
+        serialize_f32: f32, serialize_f64: f64
+    );
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.write(v as u32)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        self.write(v.len())?;
+        self.writer.write_all(v)?;
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.write(false)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        self.write(true)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, index: u32, _variant: &'static str) -> Result<()> {
+        self.write_tag(index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, index: u32, _variant: &'static str, value: &T) -> Result<()> {
+        self.write_tag(index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self> {
+        let len = len.ok_or_else(|| Error::Serde("the length of sequences has to be known up front".into()))?;
+        self.write(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self> {
+        self.write(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, index: u32, _variant: &'static str, _len: usize) -> Result<Self> {
+        self.write_tag(index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self> {
+        let len = len.ok_or_else(|| Error::Serde("the length of maps has to be known up front".into()))?;
+        self.write(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, index: u32, _variant: &'static str, _len: usize) -> Result<Self> {
+        self.write_tag(index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+// Compound values are written element by element, without any separators
+macro_rules! serialize_compound {
+    ($($trait:ident::$method:ident($($key:ident: $key_ty:ty),*)),*) => {$(
+        impl<W: Writer> ser::$trait for &mut Serializer<W> {
+            type Ok = ();
+            type Error = Error;
+
+            fn $method<T: Serialize + ?Sized>(&mut self, $($key: $key_ty,)* value: &T) -> Result<()> {
+                $(let _ = $key;)*
+                value.serialize(&mut **self)
+            }
+
+            fn end(self) -> Result<()> {
+                Ok(())
+            }
+        }
+    )*};
+}
+
+serialize_compound!(
+    SerializeSeq::serialize_element(),
+    SerializeTuple::serialize_element(),
+    SerializeTupleStruct::serialize_field(),
+    SerializeTupleVariant::serialize_field(),
+    SerializeStruct::serialize_field(key: &'static str),
+    SerializeStructVariant::serialize_field(key: &'static str)
+);
+
+impl<W: Writer> ser::SerializeMap for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub struct Deserializer<R> {
+    reader: R,
+    config: Config,
+}
+
+impl<R: Reader> Deserializer<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_config(reader, Config::default())
+    }
+
+    pub fn with_config(reader: R, config: Config) -> Self {
+        Self { reader, config }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read<T: Transmittable>(&mut self) -> Result<T> {
+        T::deserialize(&mut self.reader)
+    }
+
+    fn read_tag(&mut self) -> Result<u32> {
+        match self.config.tag {
+            TagWidth::U8 => self.read::<u8>().map(u32::from),
+            TagWidth::U16 => self.read::<u16>().map(u32::from),
+            TagWidth::U32 => self.read::<u32>(),
+        }
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>> {
+        self.read::<Vec<u8>>()
+    }
+
+    fn not_self_describing() -> Error {
+        Error::Serde("the format is not self-describing".into())
+    }
+}
+
+macro_rules! deserialize_primitives {
+    ($($method:ident => $visit:ident),*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            visitor.$visit(self.read()?)
+        }
+    )*};
+}
+
+impl<'de, R: Reader> de::Deserializer<'de> for &mut Deserializer<R> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(Deserializer::<R>::not_self_describing())
+    }
+
+    deserialize_primitives!(
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8, deserialize_i16 => visit_i16, deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64, deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8, deserialize_u16 => visit_u16, deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64, deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32, deserialize_f64 => visit_f64
+    );
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let code = self.read::<u32>()?;
+        let c = char::from_u32(code).ok_or_else(|| Error::Serde(format!("invalid char {code:#x}")))?;
+
+        visitor.visit_char(c)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_string(String::from_utf8(self.read_bytes()?)?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_byte_buf(self.read_bytes()?)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.read::<bool>()? {
+            true => visitor.visit_some(self),
+            false => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let remaining = self.read::<usize>()?;
+        visitor.visit_seq(Access { de: self, remaining })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        let actual = self.read::<usize>()?;
+        if actual != len {
+            return Err(Error::LengthMismatch { expected: len, actual });
+        }
+
+        visitor.visit_seq(Access { de: self, remaining: len })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, len: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(Access { de: self, remaining: len })
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let remaining = self.read::<usize>()?;
+        visitor.visit_map(Access { de: self, remaining })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(Access { de: self, remaining: fields.len() })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(Deserializer::<R>::not_self_describing())
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(Deserializer::<R>::not_self_describing())
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+// Reads a known amount of elements (or key-value pairs)
+struct Access<'a, R> {
+    de: &'a mut Deserializer<R>,
+    remaining: usize,
+}
+
+impl<'de, R: Reader> de::SeqAccess<'de> for Access<'_, R> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de, R: Reader> de::MapAccess<'de> for Access<'_, R> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de, R: Reader> de::EnumAccess<'de> for &mut Deserializer<R> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let tag = self.read_tag()?;
+        let variant = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(tag))?;
+
+        Ok((variant, self))
+    }
+}
+
+impl<'de, R: Reader> de::VariantAccess<'de> for &mut Deserializer<R> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(Access { de: self, remaining: len })
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(Access { de: self, remaining: fields.len() })
+    }
+}
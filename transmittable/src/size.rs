@@ -0,0 +1,84 @@
+//! Helpers for computing the encoded size of a value.
+//!
+//! The `const fn`s in this module are used by the derive macro to compute [`Transmittable::FIXED_SIZE`](crate::Transmittable::FIXED_SIZE),
+//! but they can be used by manual implementations as well.
+
+/// A writer which discards everything written to it, only keeping track of the amount of bytes.
+#[derive(Default, Debug)]
+pub struct SizeCounter(usize);
+
+impl SizeCounter {
+    pub fn count(&self) -> usize {
+        self.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for SizeCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl crate::Writer for SizeCounter {
+    fn write_all(&mut self, buf: &[u8]) -> crate::Result<()> {
+        self.0 += buf.len();
+        Ok(())
+    }
+}
+
+/// Sums up the sizes, returning `None` if any of them is `None`.
+pub const fn sum(sizes: &[Option<usize>]) -> Option<usize> {
+    let mut total = 0;
+    let mut i = 0;
+
+    while i < sizes.len() {
+        match sizes[i] {
+            Some(size) => total += size,
+            None => return None,
+        }
+
+        i += 1;
+    }
+
+    Some(total)
+}
+
+/// Returns the size if all of the sizes are known and equal, `None` otherwise.
+///
+/// This is used for enums, which only have a fixed size if all of their variants encode to the same amount of bytes.
+pub const fn same(sizes: &[Option<usize>]) -> Option<usize> {
+    if sizes.is_empty() {
+        return Some(0);
+    }
+
+    let Some(first) = sizes[0] else {
+        return None;
+    };
+
+    let mut i = 1;
+    while i < sizes.len() {
+        match sizes[i] {
+            Some(size) if size == first => (),
+            _ => return None,
+        }
+
+        i += 1;
+    }
+
+    Some(first)
+}
+
+/// Multiplies the size by `count`, returning `None` if the size isn't known.
+pub const fn repeat(size: Option<usize>, count: usize) -> Option<usize> {
+    match size {
+        Some(size) => Some(size * count),
+        None => None,
+    }
+}
@@ -0,0 +1,256 @@
+//! String encodings used by C-derived protocols, next to the length-prefixed `String` implementation.
+//!
+//! - [`NulTerminated<T>`] writes the bytes followed by a NUL byte, optionally bounding how many bytes are read before
+//!   giving up with [`Error::Unterminated`]. [`CString`] is encoded the same way.
+//! - [`FixedStr<N, PAD>`] always takes up `N` bytes, filling the unused ones at the end with `PAD` (`0` by default),
+//!   like a `char[N]` field. Trailing padding is stripped when decoding, so strings ending with the padding byte don't
+//!   survive a round trip. With the default NUL padding, strings end at the first NUL like in C: whatever follows it
+//!   is ignored when decoding, and strings containing NUL bytes are rejected when encoding.
+//!
+//! Fields of derived types can use these encodings without being wrapped with `#[transmittable(nul_terminated)]`
+//! (or `nul_terminated = MAX`) and `#[transmittable(fixed = N)]` (optionally with `pad = b' '`).
+
+use alloc::ffi::CString;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+use crate::{Error, Reader, Result, Transmittable, Writer};
+
+/// Byte strings, which can be encoded without a length prefix.
+pub trait Text: Sized {
+    fn as_bytes(&self) -> &[u8];
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self>;
+}
+
+impl Text for String {
+    fn as_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        Ok(String::from_utf8(bytes)?)
+    }
+}
+
+impl Text for Vec<u8> {
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        Ok(bytes)
+    }
+}
+
+/// A string followed by a NUL byte, which can be at most `MAX` bytes long (excluding the terminator).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NulTerminated<T, const MAX: usize = { usize::MAX }>(pub T);
+
+impl<T, const MAX: usize> NulTerminated<T, MAX> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Text, const MAX: usize> NulTerminated<T, MAX> {
+    /// Serializes a value which isn't wrapped, failing if it contains a NUL byte or is longer than `MAX`.
+    pub fn serialize_inner<W: Writer>(value: &T, writer: &mut W) -> Result<()> {
+        let bytes = value.as_bytes();
+        if let Some(position) = bytes.iter().position(|&byte| byte == 0) {
+            return Err(Error::InteriorNul(position));
+        }
+
+        if bytes.len() > MAX {
+            return Err(Error::StringTooLong { len: bytes.len(), max: MAX });
+        }
+
+        writer.write_all(bytes)?;
+        writer.write_all(&[0])?;
+        Ok(())
+    }
+
+    pub fn encoded_len_inner(value: &T) -> usize {
+        value.as_bytes().len() + 1
+    }
+}
+
+impl<T, const MAX: usize> Deref for NulTerminated<T, MAX> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T, const MAX: usize> DerefMut for NulTerminated<T, MAX> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Text, const MAX: usize> Transmittable for NulTerminated<T, MAX> {
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Self::serialize_inner(&self.0, writer)
+    }
+
+    // Readers aren't buffered, so the terminator can only be found by reading a byte at a time
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let mut buf = Vec::new();
+
+        loop {
+            let byte = u8::deserialize(reader)?;
+            if byte == 0 {
+                break;
+            }
+
+            if buf.len() == MAX {
+                return Err(Error::Unterminated { max: MAX });
+            }
+
+            buf.push(byte);
+        }
+
+        T::from_bytes(buf).map(Self)
+    }
+
+    fn encoded_len(&self) -> usize {
+        Self::encoded_len_inner(&self.0)
+    }
+}
+
+impl Transmittable for CString {
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(self.as_bytes_with_nul())?;
+        Ok(())
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let bytes = NulTerminated::<Vec<u8>>::deserialize(reader)?.into_inner();
+        Ok(CString::new(bytes).unwrap_or_else(|_| unreachable!("the terminator ends the string")))
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.as_bytes_with_nul().len()
+    }
+}
+
+/// A string taking up exactly `N` bytes, padded at the end with `PAD`.
+///
+/// When `PAD` is NUL, the string ends at the first NUL byte, so it can't contain any.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FixedStr<const N: usize, const PAD: u8 = 0>(pub String);
+
+impl<const N: usize, const PAD: u8> FixedStr<N, PAD> {
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+
+    /// Serializes a string which isn't wrapped, failing if it's longer than `N` bytes or if it contains a NUL byte
+    /// while `PAD` is NUL.
+    pub fn serialize_inner<W: Writer>(value: &str, writer: &mut W) -> Result<()> {
+        if value.len() > N {
+            return Err(Error::StringTooLong { len: value.len(), max: N });
+        }
+
+        // NUL padded strings end at the first NUL, so they can't contain one
+        let nul = match PAD {
+            0 => value.bytes().position(|byte| byte == 0),
+            _ => None,
+        };
+        if let Some(position) = nul {
+            return Err(Error::InteriorNul(position));
+        }
+
+        writer.write_all(value.as_bytes())?;
+        writer.write_all(&alloc::vec![PAD; N - value.len()])?;
+        Ok(())
+    }
+
+    pub fn encoded_len_inner(_value: &str) -> usize {
+        N
+    }
+
+    fn from_padded(mut buf: Vec<u8>) -> Result<Self> {
+        // NUL padded strings end at the first NUL like in C, the bytes following it don't have to be NULs as well
+        let len = match PAD {
+            0 => buf.iter().position(|&byte| byte == 0).unwrap_or(buf.len()),
+            _ => buf.iter().rposition(|&byte| byte != PAD).map_or(0, |last| last + 1),
+        };
+        buf.truncate(len);
+        Ok(Self(String::from_utf8(buf)?))
+    }
+}
+
+impl<const N: usize, const PAD: u8> Deref for FixedStr<N, PAD> {
+    type Target = String;
+
+    fn deref(&self) -> &String {
+        &self.0
+    }
+}
+
+impl<const N: usize, const PAD: u8> DerefMut for FixedStr<N, PAD> {
+    fn deref_mut(&mut self) -> &mut String {
+        &mut self.0
+    }
+}
+
+impl<const N: usize, const PAD: u8> Transmittable for FixedStr<N, PAD> {
+    const FIXED_SIZE: Option<usize> = Some(N);
+
+    fn serialize<W: Writer>(&self, writer: &mut W) -> Result<()> {
+        Self::serialize_inner(&self.0, writer)
+    }
+
+    fn deserialize<R: Reader>(reader: &mut R) -> Result<Self> {
+        let mut buf = alloc::vec![0u8; N];
+        reader.read_exact(&mut buf)?;
+        Self::from_padded(buf)
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod asynchronous {
+    use alloc::ffi::CString;
+    use alloc::vec::Vec;
+    use tokio::io::AsyncReadExt;
+    use crate::asynchronous::{AsyncRead, AsyncTransmittable};
+    use crate::{Error, Result};
+    use super::{FixedStr, NulTerminated, Text};
+
+    impl<T: Text + Send + Sync, const MAX: usize> AsyncTransmittable for NulTerminated<T, MAX> {
+        async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+            let mut buf = Vec::new();
+
+            loop {
+                let byte = reader.read_u8().await?;
+                if byte == 0 {
+                    break;
+                }
+
+                if buf.len() == MAX {
+                    return Err(Error::Unterminated { max: MAX });
+                }
+
+                buf.push(byte);
+            }
+
+            T::from_bytes(buf).map(Self)
+        }
+    }
+
+    impl AsyncTransmittable for CString {
+        async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+            let bytes = NulTerminated::<Vec<u8>>::deserialize_async(reader).await?.into_inner();
+            Ok(CString::new(bytes).unwrap_or_else(|_| unreachable!("the terminator ends the string")))
+        }
+    }
+
+    impl<const N: usize, const PAD: u8> AsyncTransmittable for FixedStr<N, PAD> {
+        async fn deserialize_async<R: AsyncRead + Unpin + Send>(reader: &mut R) -> Result<Self> {
+            let mut buf = alloc::vec![0u8; N];
+            reader.read_exact(&mut buf).await?;
+            Self::from_padded(buf)
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use crate::Error;
+#![allow(clippy::approx_constant)] // the float test cases are intentionally not using the consts
+
+use crate::{Error, Transmittable};
 use transmittable_macros::read_and_write;
 
 read_and_write!(bool;
@@ -58,4 +60,1455 @@ read_and_write!(Option<u8>;
     (b"\x01\x00", Ok(Some(0u8))),
     (b"\x01\xFF", Ok(Some(255u8))),
     (b"\x02", Err(Error::InvalidBoolean(2))),
-);
\ No newline at end of file
+);
+
+read_and_write!(std::ops::Range<u64>;
+    (b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00", Ok(0..0)),
+    (b"\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x01\x00", Ok(1..256)),
+);
+
+read_and_write!(std::ops::RangeInclusive<u32>;
+    (b"\x00\x00\x00\x01\x00\x00\x00\x0A", Ok(1..=10)),
+);
+
+read_and_write!(std::ops::RangeFrom<u8>; (b"\x05", Ok(5..)));
+read_and_write!(std::ops::RangeTo<u8>; (b"\x05", Ok(..5)));
+read_and_write!(std::ops::RangeToInclusive<u8>; (b"\x05", Ok(..=5)));
+read_and_write!(std::ops::RangeFull; (b"", Ok(..)));
+
+read_and_write!(std::ops::Bound<u16>;
+    (b"\x00\x01\x00", Ok(std::ops::Bound::Included(256))),
+    (b"\x01\x00\x01", Ok(std::ops::Bound::Excluded(1))),
+    (b"\x02",         Ok(std::ops::Bound::Unbounded)),
+    (b"\x03",         Err(Error::InvalidEnumVariant)),
+);
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(async, schema)]
+struct Point {
+    x: u16,
+    y: u16,
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(async, schema)]
+struct Labeled(u8, String);
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(async, schema)]
+#[repr(u8)]
+enum Shape {
+    Empty,
+    Circle(u16),
+    Rect { w: u8, h: u8 },
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(schema)]
+#[repr(u16)]
+enum Direction {
+    Up,
+    Down = 5,
+    Left,
+}
+
+read_and_write!(Point; (b"\x00\x01\x00\x02", Ok(Point { x: 1, y: 2 })));
+read_and_write!(Labeled; (b"\x07\x00\x00\x00\x00\x00\x00\x00\x02hi", Ok(Labeled(7, "hi".to_string()))));
+
+read_and_write!(Shape;
+    (b"\x00",         Ok(Shape::Empty)),
+    (b"\x01\x01\x00", Ok(Shape::Circle(256))),
+    (b"\x02\x03\x04", Ok(Shape::Rect { w: 3, h: 4 })),
+    (b"\x03",         Err(Error::InvalidEnumVariant)),
+);
+
+read_and_write!(Direction;
+    (b"\x00\x00", Ok(Direction::Up)),
+    (b"\x00\x05", Ok(Direction::Down)),
+    (b"\x00\x06", Ok(Direction::Left)),
+);
+
+#[test]
+fn fixed_size() {
+    assert_eq!(u32::FIXED_SIZE, Some(4));
+    assert_eq!(<[u16; 3]>::FIXED_SIZE, Some(size_of::<usize>() + 6));
+    assert_eq!(<Vec<u8>>::FIXED_SIZE, None);
+    assert_eq!(<Option<u8>>::FIXED_SIZE, None);
+    assert_eq!(<std::ops::Bound<u8>>::FIXED_SIZE, None);
+    assert_eq!(<std::ops::Bound<std::ops::RangeFull>>::FIXED_SIZE, Some(1));
+    assert_eq!(Point::FIXED_SIZE, Some(4));
+    assert_eq!(Labeled::FIXED_SIZE, None);
+    assert_eq!(Shape::FIXED_SIZE, None);
+    assert_eq!(Direction::FIXED_SIZE, Some(2));
+}
+
+#[test]
+fn slice_helpers() {
+    let point = Point { x: 1, y: 2 };
+    assert_eq!(crate::to_vec(&point).unwrap(), b"\x00\x01\x00\x02");
+
+    let mut buf = [0xFFu8; 6];
+    assert_eq!(crate::to_slice(&point, &mut buf), Ok(4));
+    assert_eq!(buf, *b"\x00\x01\x00\x02\xFF\xFF");
+    assert_eq!(crate::to_slice(&point, &mut buf[..3]), Err(Error::BufferTooSmall { needed: 4, available: 3 }));
+
+    assert_eq!(crate::from_slice::<Point>(&buf), Ok((point.clone(), 4)));
+    assert_eq!(crate::from_slice_exact::<Point>(&buf[..4]), Ok(point));
+    assert_eq!(crate::from_slice_exact::<Point>(&buf), Err(Error::TrailingBytes(2)));
+}
+
+#[test]
+fn io_errors() {
+    use std::io::{Error as IoError, ErrorKind};
+
+    // running out of input or space is reported the same way as without `std`
+    assert_eq!(crate::from_slice::<Point>(b"\x00\x01"), Err(Error::UnexpectedEof));
+    assert_eq!(Point { x: 1, y: 2 }.serialize(&mut &mut [0u8; 3][..]), Err(Error::WriteZero));
+    assert_eq!(Error::from(IoError::from(ErrorKind::UnexpectedEof)), Error::UnexpectedEof);
+    assert!(matches!(Error::from(IoError::from(ErrorKind::BrokenPipe)), Error::IOError(e) if e.kind() == ErrorKind::BrokenPipe));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn async_round_trip() {
+    use crate::AsyncTransmittable;
+
+    let value = (
+        vec![Shape::Empty, Shape::Circle(3), Shape::Rect { w: 1, h: 2 }],
+        Labeled(1, "async".to_string()),
+        Some(std::ops::Bound::Excluded(Point { x: 3, y: 4 })),
+    );
+
+    let mut buf = Vec::new();
+    value.0.serialize_async(&mut buf).await.unwrap();
+    value.1.serialize_async(&mut buf).await.unwrap();
+    value.2.serialize_async(&mut buf).await.unwrap();
+
+    let expected = [crate::to_vec(&value.0).unwrap(), crate::to_vec(&value.1).unwrap(), crate::to_vec(&value.2).unwrap()].concat();
+    assert_eq!(buf, expected);
+
+    let mut reader = buf.as_slice();
+    assert_eq!(Vec::<Shape>::deserialize_async(&mut reader).await, Ok(value.0));
+    assert_eq!(Labeled::deserialize_async(&mut reader).await, Ok(value.1));
+    assert_eq!(Option::<std::ops::Bound<Point>>::deserialize_async(&mut reader).await, Ok(value.2));
+    assert!(reader.is_empty());
+
+    let login = Login { user: "root".into(), password: "hunter2".into(), region: "eu".into() };
+    let bytes = crate::to_vec(&login).unwrap();
+    assert_eq!(Login::deserialize_async(&mut bytes.as_slice()).await, Ok(login));
+
+    let bytes = crate::to_vec(&Command::Say("hi".into())).unwrap();
+    assert_eq!(Command::deserialize_async(&mut bytes.as_slice()).await, Ok(Command::Say("hi".into())));
+
+    let record = Record { name: "ab".into(), scores: vec![1, 2], tag: 3 };
+    let bytes = crate::to_vec(&record).unwrap();
+    assert_eq!(Record::deserialize_async(&mut bytes.as_slice()).await, Ok(record));
+
+    // the futures have to be `Send` to be usable from spawned tasks
+    fn assert_send<T: Send>(_: &T) {}
+    assert_send(&Shape::deserialize_async(&mut reader));
+}
+
+// Only implements `Transmittable`, deriving on types holding it has to work whichever features are enabled, as
+// `AsyncTransmittable` and `Schema` are only implemented when asked for
+#[derive(Debug, Clone, PartialEq)]
+struct Handwritten(u8);
+
+impl Transmittable for Handwritten {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
+    fn serialize<W: crate::Writer>(&self, writer: &mut W) -> crate::Result<()> {
+        self.0.serialize(writer)
+    }
+
+    fn deserialize<R: crate::Reader>(reader: &mut R) -> crate::Result<Self> {
+        u8::deserialize(reader).map(Self)
+    }
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+struct Holder {
+    inner: Handwritten,
+}
+
+read_and_write!(Holder; (b"\x05", Ok(Holder { inner: Handwritten(5) })));
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn async_packet_sets() {
+    use crate::AsyncTransmittable;
+
+    let packet = PlayPackets::Chat(Chat("hi".into()));
+    let bytes = crate::to_vec(&packet).unwrap();
+    assert_eq!(PlayPackets::deserialize_async(&mut bytes.as_slice()).await, Ok(packet));
+}
+
+#[cfg(feature = "tokio-util")]
+#[test]
+fn codec_frames() {
+    use crate::TransmittableCodec;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    let mut codec = TransmittableCodec::<Labeled>::new();
+    let mut buf = bytes::BytesMut::new();
+    codec.encode(Labeled(1, "one".to_string()), &mut buf).unwrap();
+    codec.encode(&Labeled(2, "two".to_string()), &mut buf).unwrap();
+    assert_eq!(&buf[..4], b"\x00\x00\x00\x0C");
+
+    // feed the frames byte by byte, partial frames must not produce an error
+    let mut src = bytes::BytesMut::new();
+    let mut decoded = Vec::new();
+    for byte in buf.iter() {
+        src.extend_from_slice(&[*byte]);
+        if let Some(item) = codec.decode(&mut src).unwrap() {
+            decoded.push(item);
+        }
+    }
+
+    assert_eq!(decoded, vec![Labeled(1, "one".to_string()), Labeled(2, "two".to_string())]);
+    assert!(src.is_empty());
+
+    let mut small = TransmittableCodec::<Labeled>::with_max_frame_length(8);
+    assert_eq!(small.encode(Labeled(1, "one".to_string()), &mut buf), Err(Error::FrameTooLarge { len: 12, max: 8 }));
+    assert_eq!(small.decode(&mut bytes::BytesMut::from(&b"\x00\x00\x00\x0C"[..])), Err(Error::FrameTooLarge { len: 12, max: 8 }));
+}
+
+#[cfg(feature = "tokio-util")]
+#[test]
+fn codec_failed_items() {
+    use crate::{NulTerminated, TransmittableCodec};
+    use tokio_util::codec::{Decoder, Encoder};
+
+    // an item failing to serialize leaves nothing behind for the next one to be appended to
+    let mut codec = TransmittableCodec::<NulTerminated<String, 8>>::new();
+    let mut buf = bytes::BytesMut::new();
+    assert_eq!(codec.encode(NulTerminated("a\0b".to_string()), &mut buf), Err(Error::InteriorNul(1)));
+    assert!(buf.is_empty());
+    codec.encode(NulTerminated("ok".to_string()), &mut buf).unwrap();
+
+    assert_eq!(codec.decode(&mut buf), Ok(Some(NulTerminated("ok".to_string()))));
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn incremental_decoder() {
+    let messages = vec![Labeled(1, "one".to_string()), Labeled(2, "two".to_string()), Labeled(3, String::new())];
+    let bytes = messages.iter().flat_map(|message| crate::to_vec(message).unwrap()).collect::<Vec<_>>();
+
+    let mut decoder = crate::IncrementalDecoder::<Labeled>::new();
+    let mut decoded = Vec::new();
+    for chunk in bytes.chunks(5) {
+        decoder.feed(chunk);
+        decoded.extend(decoder.by_ref().map(Result::unwrap));
+    }
+
+    assert_eq!(decoded, messages);
+    assert!(decoder.buffered().is_empty());
+
+    decoder.feed(b"\x00\x00\x00\x00\x00\x00\x00\x00\x01\xFF");
+    assert!(matches!(decoder.decode(), Err(Error::Utf8DecodeError(_))));
+}
+
+#[test]
+fn incremental_decoder_errors() {
+    let mut decoder = crate::IncrementalDecoder::<Labeled>::new();
+    decoder.feed(b"\x00\x00\x00\x00\x00\x00\x00\x00\x01\xFF");
+
+    // iterating stops after the error instead of yielding it forever
+    let results = decoder.by_ref().collect::<Vec<_>>();
+    assert!(matches!(results[..], [Err(Error::Utf8DecodeError(_))]));
+    assert!(decoder.has_failed());
+    assert_eq!(decoder.buffered().len(), 10);
+}
+
+#[test]
+fn incremental_decoder_non_blocking() {
+    // a reader returning one chunk per readiness event, like a non-blocking socket would
+    struct Socket(Vec<Vec<u8>>);
+
+    impl std::io::Read for Socket {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                return Ok(0);
+            }
+
+            // an empty chunk marks the point where the socket has no more data ready
+            match self.0.remove(0) {
+                chunk if chunk.is_empty() => Err(std::io::ErrorKind::WouldBlock.into()),
+                chunk => {
+                    buf[..chunk.len()].copy_from_slice(&chunk);
+                    Ok(chunk.len())
+                },
+            }
+        }
+    }
+
+    let mut socket = Socket(vec![vec![0, 1], vec![], vec![0], vec![], vec![2, 0, 3], vec![]]);
+    let mut decoder = crate::IncrementalDecoder::<Point>::new();
+
+    assert_eq!(decoder.read_from(&mut socket).unwrap(), 2);
+    assert_eq!(decoder.decode(), Ok(None));
+    assert_eq!(decoder.read_from(&mut socket).unwrap(), 1);
+    assert_eq!(decoder.decode(), Ok(None));
+    assert_eq!(decoder.read_from(&mut socket).unwrap(), 3);
+    assert_eq!(decoder.decode(), Ok(Some(Point { x: 1, y: 2 })));
+    assert_eq!(decoder.buffered(), &[0, 3]);
+    assert_eq!(decoder.read_from(&mut socket).unwrap(), 0);
+}
+
+#[test]
+fn framed_round_trip() {
+    use crate::{FrameConfig, FramedReader, FramedWriter, HeaderWidth};
+
+    let config = FrameConfig::new(HeaderWidth::U16, 64);
+    let mut writer = FramedWriter::with_config(Vec::new(), config);
+    writer.write(&Point { x: 1, y: 2 }).unwrap();
+    writer.write(&Point { x: 3, y: 4 }).unwrap();
+
+    let bytes = writer.into_inner();
+    assert_eq!(bytes, b"\x00\x04\x00\x01\x00\x02\x00\x04\x00\x03\x00\x04");
+
+    let reader = FramedReader::<_, Point>::with_config(bytes.as_slice(), config);
+    assert_eq!(reader.collect::<crate::Result<Vec<_>>>(), Ok(vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]));
+
+    // a truncated frame is an error rather than the end of the stream
+    let mut reader = FramedReader::<_, Point>::with_config(&bytes[..3], config);
+    assert!(reader.next().is_some_and(|result| result.is_err_and(|e| e.is_eof())));
+}
+
+#[test]
+fn framed_errors() {
+    use crate::{read_framed, write_framed, FrameConfig, HeaderWidth};
+
+    let config = FrameConfig::new(HeaderWidth::U8, 1024);
+    assert_eq!(write_framed(&mut Vec::new(), &vec![0u8; 256], &config), Err(Error::FrameTooLarge { len: 264, max: 255 }));
+    assert_eq!(read_framed::<_, Point>(&mut &b"\x05\x00\x01\x00\x02\xFF"[..], &config), Err(Error::TrailingBytes(1)));
+    assert_eq!(read_framed::<_, u8>(&mut &b"\x05"[..], &FrameConfig::new(HeaderWidth::U8, 4)), Err(Error::FrameTooLarge { len: 5, max: 4 }));
+}
+
+#[test]
+fn seq_streaming() {
+    use crate::{write_seq, SeqReader, SeqWriter};
+
+    let points = (0..100).map(|i| Point { x: i, y: i * 2 }).collect::<Vec<_>>();
+    let bytes = crate::to_vec(&points).unwrap();
+
+    let mut streamed = Vec::new();
+    write_seq(&mut streamed, points.iter()).unwrap();
+    assert_eq!(streamed, bytes);
+
+    let reader = SeqReader::<_, Point>::new(bytes.as_slice()).unwrap();
+    assert_eq!(reader.remaining(), 100);
+    assert_eq!(reader.collect::<crate::Result<Vec<_>>>(), Ok(points));
+
+    let mut writer = SeqWriter::new(Vec::new(), 1).unwrap();
+    writer.write(&1u8).unwrap();
+    assert_eq!(writer.write(&2u8), Err(Error::LengthMismatch { expected: 1, actual: 2 }));
+    assert_eq!(SeqWriter::new(Vec::new(), 2).unwrap().finish(), Err(Error::LengthMismatch { expected: 2, actual: 0 }));
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(packet_id = 0x01, async, schema)]
+struct KeepAlive {
+    id: u32,
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(packet_id = 0x21, async, schema)]
+struct Chat(String);
+
+crate::packets! {
+    #[derive(Debug, Clone, PartialEq)]
+    #[transmittable(async, schema)]
+    enum PlayPackets: u8 {
+        KeepAlive(KeepAlive),
+        Chat(Chat),
+    }
+}
+
+read_and_write!(PlayPackets;
+    (b"\x01\x00\x00\x00\x07",                   Ok(PlayPackets::KeepAlive(KeepAlive { id: 7 }))),
+    (b"\x21\x00\x00\x00\x00\x00\x00\x00\x02hi", Ok(PlayPackets::Chat(Chat("hi".to_string())))),
+    (b"\x02",                                   Err(Error::UnknownPacketId(2))),
+);
+
+#[test]
+fn packet_sets() {
+    use crate::{Packet, PacketSet};
+
+    assert_eq!(Chat::ID, 0x21);
+    assert_eq!(PlayPackets::from(KeepAlive { id: 1 }).id(), 0x01);
+    assert!(PlayPackets::contains_id(0x21));
+    assert!(!PlayPackets::contains_id(0x02));
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(packet_id = 0x00)]
+struct Handshake {
+    version: u16,
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(packet_id = 0x00)]
+struct LoginStart(String);
+
+crate::packets! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum HandshakePackets: u8 {
+        Handshake(Handshake),
+    }
+}
+
+crate::packets! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum LoginPackets: u8 {
+        LoginStart(LoginStart),
+        KeepAlive(KeepAlive),
+    }
+}
+
+crate::protocol! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum Serverbound(ConnectionState) {
+        Handshake(HandshakePackets),
+        Login(LoginPackets),
+        Play(PlayPackets),
+    }
+}
+
+#[test]
+fn protocol_states() {
+    use crate::Protocol;
+
+    // the same id means a different packet depending on the state
+    let bytes = b"\x00\x00\x00\x00\x00\x00\x00\x00\x00";
+    assert_eq!(Serverbound::deserialize_in(ConnectionState::Handshake, &mut &bytes[..3]), Ok(Serverbound::Handshake(HandshakePackets::Handshake(Handshake { version: 0 }))));
+    assert_eq!(Serverbound::deserialize_in(ConnectionState::Login, &mut &bytes[..]), Ok(Serverbound::Login(LoginPackets::LoginStart(LoginStart(String::new())))));
+    assert_eq!(Serverbound::deserialize_in(ConnectionState::Play, &mut &bytes[..]), Err(Error::InvalidPacketForState { id: 0, state: "Play" }));
+
+    let packet = Serverbound::Play(PlayPackets::Chat(Chat("hi".to_string())));
+    assert_eq!(packet.state(), ConnectionState::Play);
+
+    let mut buf = Vec::new();
+    packet.serialize_in(ConnectionState::Play, &mut buf).unwrap();
+    assert_eq!(Serverbound::deserialize_in(ConnectionState::Play, &mut buf.as_slice()), Ok(packet.clone()));
+    assert_eq!(packet.serialize_in(ConnectionState::Login, &mut buf), Err(Error::InvalidPacketForState { id: 0x21, state: "Login" }));
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn schema_layouts() {
+    use crate::schema::{EnumLayout, FieldLayout, Integer, Layout, Length, Schema, StructLayout, VariantLayout};
+
+    let u16_layout = Layout::Integer(Integer::new(false, 16));
+    assert_eq!(Point::schema(), Layout::Struct(StructLayout::new("Point", [
+        FieldLayout::new("x", u16_layout.clone()),
+        FieldLayout::new("y", u16_layout.clone()),
+    ])));
+
+    assert_eq!(Labeled::schema(), Layout::Struct(StructLayout::new("Labeled", [
+        FieldLayout::new("0", Layout::Integer(Integer::new(false, 8))),
+        FieldLayout::new("1", Layout::String { length: Length::Prefixed(Integer::new(false, usize::BITS)) }),
+    ])));
+
+    assert_eq!(Direction::schema(), Layout::Enum(EnumLayout::new("Direction", Integer::new(false, 16), [
+        VariantLayout::new("Up", 0, []),
+        VariantLayout::new("Down", 5, []),
+        VariantLayout::new("Left", 6, []),
+    ])));
+
+    let Layout::Enum(packets) = PlayPackets::schema() else { panic!("packet sets are described as enums") };
+    assert_eq!(packets.tag, Integer::new(false, 8));
+    assert_eq!(packets.variants.iter().map(|variant| variant.tag).collect::<Vec<_>>(), [0x01, 0x21]);
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn schema_exports() {
+    use crate::schema::Schema;
+
+    assert_eq!(Point::schema().size(), Some(4));
+    assert_eq!(Shape::schema().size(), None);
+    assert_eq!(Direction::schema().size(), Some(2));
+
+    assert_eq!(
+        Point::schema().json().to_string(),
+        concat!(
+            r#"{"kind":"struct","size":4,"name":"Point","fields":["#,
+            r#"{"name":"x","offset":0,"layout":{"kind":"integer","size":2,"signed":false,"bits":16,"endian":"big"}},"#,
+            r#"{"name":"y","offset":2,"layout":{"kind":"integer","size":2,"signed":false,"bits":16,"endian":"big"}}"#,
+            "]}",
+        ),
+    );
+
+    let json = Labeled::schema().json().to_string();
+    assert!(json.contains(r#"{"name":"1","offset":1,"layout":{"kind":"string","size":null"#));
+
+    let markdown = Shape::schema().markdown().to_string();
+    assert!(markdown.contains("## `Shape`\n\nSize: variable\n\nTag: u8, field offsets include the tag.\n"));
+    assert!(markdown.contains("### `Shape::Empty` (tag `0`)\n\nNo fields.\n"));
+    assert!(markdown.contains("| Offset | Field | Type | Size |\n| --- | --- | --- | --- |\n| 1 | w | u8 | 1 |\n| 2 | h | u8 | 1 |\n"));
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn schema_arrays() {
+    use crate::schema::{Integer, Layout, Length, Schema};
+
+    let length = Length::Fixed { count: 3, prefix: Integer::new(false, usize::BITS) };
+    assert_eq!(<[u16; 3]>::schema(), Layout::Sequence { length, element: Box::new(u16::schema()) });
+
+    // the count is part of the layout, so arrays have a size just like their `FIXED_SIZE`
+    assert_eq!(<[u16; 3]>::schema().size(), Some(14));
+    assert_eq!(<[u16; 3]>::schema().size(), <[u16; 3]>::FIXED_SIZE);
+    assert_eq!(<[String; 2]>::schema().size(), None);
+    assert_ne!(<[u8; 4]>::schema(), <[u8; 8]>::schema());
+}
+
+// a fixed-size array followed by another field
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(schema)]
+struct Header {
+    magic: [u8; 4],
+    version: u16,
+}
+
+read_and_write!(Header; (b"\x00\x00\x00\x00\x00\x00\x00\x04TRNS\x00\x02", Ok(Header { magic: *b"TRNS", version: 2 })));
+
+#[cfg(feature = "schema")]
+#[test]
+fn schema_array_exports() {
+    use crate::schema::Schema;
+
+    // the array has a size, so the field following it has an offset
+    assert_eq!(Header::schema().size(), Header::FIXED_SIZE);
+
+    let json = Header::schema().json().to_string();
+    assert!(json.starts_with(r#"{"kind":"struct","size":14,"name":"Header","fields":[{"name":"magic","offset":0,"layout":{"kind":"sequence","size":12,"#));
+    assert!(json.contains(r#""length":{"kind":"fixed","count":4,"integer":{"kind":"integer","size":8,"#));
+    assert!(json.ends_with(r#"{"name":"version","offset":12,"layout":{"kind":"integer","size":2,"signed":false,"bits":16,"endian":"big"}}]}"#));
+
+    let markdown = Header::schema().markdown().to_string();
+    assert!(markdown.contains("| 0 | magic | sequence of u8, 4 items with a u64 length prefix | 12 |\n| 12 | version | u16 | 2 |\n"));
+}
+
+#[cfg(feature = "schema")]
+mod v2 {
+    use crate::Transmittable;
+
+    #[derive(Transmittable)]
+    #[transmittable(schema)]
+    pub struct Point {
+        pub y: u16,
+        pub x: u32,
+    }
+
+    #[derive(Transmittable)]
+    #[transmittable(schema)]
+    #[repr(u16)]
+    pub enum Direction {
+        Up,
+        Left = 5,
+        Sideways,
+    }
+
+    // `Point` and `Direction` with renamed fields and variants
+    #[derive(Transmittable)]
+    #[transmittable(schema)]
+    pub struct Coordinates {
+        pub left: u16,
+        pub top: u16,
+    }
+
+    #[derive(Transmittable)]
+    #[transmittable(schema)]
+    #[repr(u16)]
+    pub enum Heading {
+        North,
+        South = 5,
+        West,
+    }
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn schema_compatibility() {
+    use crate::schema::{assert_wire_compatible, breaking_changes, BreakingChange, Change, Integer, Schema};
+
+    assert_wire_compatible::<Point, Point>();
+    assert_wire_compatible::<Shape, Shape>();
+
+    let change = |path: &str, change| BreakingChange { path: path.into(), change };
+
+    let changes = breaking_changes(&Point::schema(), &v2::Point::schema());
+    assert_eq!(changes, [
+        change("Point", Change::FieldMoved { name: "x".into(), old: 0, new: 1 }),
+        change("Point", Change::FieldMoved { name: "y".into(), old: 1, new: 0 }),
+        change("Point.x", Change::IntegerChanged { old: Integer::new(false, 16), new: Integer::new(false, 32) }),
+    ]);
+    assert_eq!(changes[2].to_string(), "Point.x: changed from u16 to u32");
+
+    assert_eq!(breaking_changes(&Direction::schema(), &v2::Direction::schema()), [
+        change("Direction", Change::TagReused { tag: 5, old: "Down".into(), new: "Left".into() }),
+        change("Direction", Change::VariantTagChanged { name: "Left".into(), old: 6, new: 5 }),
+        change("Direction", Change::TagReused { tag: 6, old: "Left".into(), new: "Sideways".into() }),
+    ]);
+
+    // the wire format is positional, names don't matter as long as nothing moves
+    assert_wire_compatible::<Point, v2::Coordinates>();
+    assert_wire_compatible::<Direction, v2::Heading>();
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn schema_array_lengths() {
+    use crate::schema::{breaking_changes, BreakingChange, Change, Integer, Length, Schema};
+
+    let fixed = |count| Length::Fixed { count, prefix: Integer::new(false, usize::BITS) };
+    assert_eq!(breaking_changes(&<[u8; 4]>::schema(), &<[u8; 4]>::schema()), []);
+
+    let changes = breaking_changes(&<[u8; 4]>::schema(), &<[u8; 8]>::schema());
+    assert_eq!(changes, [BreakingChange { path: "value".into(), change: Change::LengthChanged { old: fixed(4), new: fixed(8) } }]);
+    assert_eq!(changes[0].to_string(), "value: the length changed from 4 items with a u64 prefix to 8 items with a u64 prefix");
+
+    // which is why decoding an array of another length fails instead of panicking
+    let bytes = crate::to_vec(&[1u8, 2, 3, 4]).unwrap();
+    assert_eq!(crate::from_slice::<[u8; 8]>(&bytes), Err(Error::LengthMismatch { expected: 8, actual: 4 }));
+}
+
+#[cfg(feature = "schema")]
+#[test]
+#[should_panic(expected = "layouts are not wire compatible:\n  - Point: field `x` moved from position 0 to 1")]
+fn schema_incompatible() {
+    crate::schema::assert_wire_compatible::<Point, v2::Point>();
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq, Default)]
+#[transmittable(version = 3, schema)]
+struct Status {
+    online: bool,
+    #[transmittable(since = 2)]
+    message: String,
+    #[transmittable(since = 3)]
+    away: Option<u16>,
+}
+
+read_and_write!(Status;
+    (b"\x01\x00\x00\x00\x00\x00\x00\x00\x02hi\x01\x00\x05", Ok(Status { online: true, message: "hi".into(), away: Some(5) })),
+);
+
+#[test]
+fn versioned_fields() {
+    use crate::Versioned;
+
+    let status = Status { online: true, message: "hi".into(), away: Some(5) };
+    let encodings: [&[u8]; 3] = [
+        b"\x01",
+        b"\x01\x00\x00\x00\x00\x00\x00\x00\x02hi",
+        b"\x01\x00\x00\x00\x00\x00\x00\x00\x02hi\x01\x00\x05",
+    ];
+
+    for (version, expected) in (1..).zip(encodings) {
+        let mut bytes = Vec::new();
+        status.serialize_versioned(version, &mut bytes).unwrap();
+        assert_eq!(bytes, expected, "version {version}");
+        assert_eq!(status.encoded_len_versioned(version), expected.len());
+    }
+
+    // fields newer than the decoded version are filled with their defaults
+    assert_eq!(Status::deserialize_versioned(1, &mut &encodings[0][..]).unwrap(), Status { online: true, ..Default::default() });
+    assert_eq!(
+        Status::deserialize_versioned(2, &mut &encodings[1][..]).unwrap(),
+        Status { online: true, message: "hi".into(), away: None },
+    );
+
+    assert_eq!(status.serialize_versioned(4, &mut Vec::new()), Err(Error::UnsupportedVersion { version: 4, max: 3 }));
+    assert_eq!(Status::deserialize_versioned(4, &mut &encodings[2][..]), Err(Error::UnsupportedVersion { version: 4, max: 3 }));
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn schema_versioned_fields() {
+    use crate::schema::{breaking_changes, FieldLayout, Layout, Schema, StructLayout};
+
+    let Layout::Struct(status) = Status::schema() else { panic!("structs are described as structs") };
+    assert_eq!(status.fields.iter().map(|field| field.since).collect::<Vec<_>>(), [None, Some(2), Some(3)]);
+
+    // fields added in later versions aren't sent to peers speaking the first one
+    let first = Layout::Struct(StructLayout::new("Status", [FieldLayout::new("online", bool::schema())]));
+    assert_eq!(breaking_changes(&first, &Status::schema()), []);
+
+    let json = Status::schema().json().to_string();
+    assert!(json.contains(r#""name":"away","offset":null,"layout":{"kind":"option","size":null"#));
+    assert!(json.ends_with(r#","since":3}]}"#));
+
+    let markdown = Status::schema().markdown().to_string();
+    assert!(markdown.contains("| 1 | message (since version 2) | UTF-8 string, u64 length prefix | variable |\n"));
+}
+
+// nested versioned types are encoded with the version of the outer one, capped at their own newest version
+#[derive(Transmittable, Debug, Clone, PartialEq, Default)]
+#[transmittable(version = 4)]
+struct Presence {
+    #[transmittable(versioned)]
+    status: Status,
+    #[transmittable(since = 4)]
+    room: u8,
+}
+
+read_and_write!(Presence;
+    (b"\x01\x00\x00\x00\x00\x00\x00\x00\x02hi\x01\x00\x05\x07", Ok(Presence { status: Status { online: true, message: "hi".into(), away: Some(5) }, room: 7 })),
+);
+
+#[test]
+fn nested_versioned_fields() {
+    use crate::Versioned;
+
+    let presence = Presence { status: Status { online: true, message: "hi".into(), away: Some(5) }, room: 7 };
+    let encodings: [&[u8]; 4] = [
+        b"\x01",
+        b"\x01\x00\x00\x00\x00\x00\x00\x00\x02hi",
+        b"\x01\x00\x00\x00\x00\x00\x00\x00\x02hi\x01\x00\x05",
+        b"\x01\x00\x00\x00\x00\x00\x00\x00\x02hi\x01\x00\x05\x07",
+    ];
+
+    for (version, expected) in (1..).zip(encodings) {
+        let mut bytes = Vec::new();
+        presence.serialize_versioned(version, &mut bytes).unwrap();
+        assert_eq!(bytes, expected, "version {version}");
+        assert_eq!(presence.encoded_len_versioned(version), expected.len());
+    }
+
+    assert_eq!(
+        Presence::deserialize_versioned(1, &mut &encodings[0][..]).unwrap(),
+        Presence { status: Status { online: true, ..Default::default() }, room: 0 },
+    );
+    assert_eq!(Presence::deserialize_versioned(3, &mut &encodings[2][..]).unwrap(), Presence { room: 0, ..presence.clone() });
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[repr(u8)]
+enum Mode {
+    Idle,
+    Active,
+    #[transmittable(other)]
+    Unknown(u8),
+}
+
+read_and_write!(Mode;
+    (b"\x00", Ok(Mode::Idle)),
+    (b"\x01", Ok(Mode::Active)),
+    (b"\x07", Ok(Mode::Unknown(7))),
+);
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(delimited, async, schema)]
+#[repr(u8)]
+enum Event {
+    Ping,
+    Move { x: u8, y: u8 },
+    #[transmittable(other)]
+    Unknown { tag: u8, payload: Vec<u8> },
+}
+
+read_and_write!(Event;
+    (b"\x00\x00\x00\x00\x00\x00\x00\x00\x00", Ok(Event::Ping)),
+    (b"\x01\x00\x00\x00\x00\x00\x00\x00\x02\x03\x04", Ok(Event::Move { x: 3, y: 4 })),
+    (b"\x09\x00\x00\x00\x00\x00\x00\x00\x03abc", Ok(Event::Unknown { tag: 9, payload: b"abc".to_vec() })),
+);
+
+#[test]
+fn delimited_enums() {
+    // fields appended to a known variant by a newer peer are skipped
+    let bytes = b"\x01\x00\x00\x00\x00\x00\x00\x00\x03\x03\x04\x05\x00";
+    assert_eq!(crate::from_slice::<Event>(bytes).unwrap(), (Event::Move { x: 3, y: 4 }, 12));
+
+    // and fields missing from the end of the payload of an older peer are defaulted, just like in delimited structs
+    let bytes = b"\x01\x00\x00\x00\x00\x00\x00\x00\x01\x03";
+    assert_eq!(crate::from_slice::<Event>(bytes).unwrap(), (Event::Move { x: 3, y: 0 }, 10));
+
+    let bytes = b"\x01\x00\x00\x00\x00\x00\x00\x00\x02\x03";
+    assert!(crate::from_slice::<Event>(bytes).unwrap_err().is_eof());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn async_delimited_enums() {
+    use crate::AsyncTransmittable;
+
+    for event in [Event::Move { x: 1, y: 2 }, Event::Unknown { tag: 9, payload: b"abc".to_vec() }] {
+        let bytes = crate::to_vec(&event).unwrap();
+        assert_eq!(Event::deserialize_async(&mut bytes.as_slice()).await, Ok(event));
+    }
+
+    let bytes = b"\x01\x00\x00\x00\x00\x00\x00\x00\x01\x03";
+    assert_eq!(Event::deserialize_async(&mut &bytes[..]).await, Ok(Event::Move { x: 3, y: 0 }));
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn schema_delimited_enums() {
+    use crate::schema::{breaking_changes, EnumLayout, FieldLayout, Integer, Layout, Length, Schema, VariantLayout};
+
+    let byte = || Layout::Integer(Integer::new(false, 8));
+    let length = Length::Prefixed(Integer::new(false, usize::BITS));
+
+    // the catch-all variant doesn't have a tag of its own
+    assert_eq!(Event::schema(), Layout::Enum(
+        EnumLayout::new("Event", Integer::new(false, 8), [
+            VariantLayout::new("Ping", 0, []),
+            VariantLayout::new("Move", 1, [FieldLayout::new("x", byte()), FieldLayout::new("y", byte())]),
+        ])
+        .delimited(length.clone())
+        .catch_all("Unknown"),
+    ));
+
+    // fields can be appended to and removed from the end of delimited variants
+    let older = Layout::Enum(
+        EnumLayout::new("Event", Integer::new(false, 8), [
+            VariantLayout::new("Ping", 0, []),
+            VariantLayout::new("Move", 1, [FieldLayout::new("x", byte())]),
+        ])
+        .delimited(length)
+        .catch_all("Unknown"),
+    );
+    assert_eq!(breaking_changes(&older, &Event::schema()), []);
+    assert_eq!(breaking_changes(&Event::schema(), &older), []);
+}
+
+#[test]
+#[cfg(feature = "schema")]
+fn schema_catch_all() {
+    use crate::schema::{breaking_changes, BreakingChange, Change, Json, Layout, Markdown, Schema};
+
+    let Layout::Enum(event) = Event::schema() else { panic!("`Event` isn't an enum") };
+    let without = Layout::Enum(crate::schema::EnumLayout { catch_all: None, ..event });
+
+    // unknown tags are only rejected after removing the catch-all
+    assert_eq!(breaking_changes(&without, &Event::schema()), []);
+    let changes = breaking_changes(&Event::schema(), &without);
+    assert_eq!(changes, [BreakingChange { path: "Event".into(), change: Change::CatchAllRemoved("Unknown".into()) }]);
+    assert_eq!(changes[0].to_string(), "Event: the catch-all variant `Unknown` was removed");
+
+    assert!(Json(&Event::schema()).to_string().ends_with(r#","catch_all":"Unknown"}"#));
+    assert!(!Json(&without).to_string().contains("catch_all"));
+    assert!(Markdown(&Event::schema()).to_string().contains("\nUnknown tags are accepted and decoded as `Event::Unknown`.\n"));
+    assert!(!Markdown(&without).to_string().contains("Unknown tags"));
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq, Default)]
+#[transmittable(delimited, schema)]
+struct Profile {
+    id: u16,
+    name: String,
+}
+
+// a newer version of `Profile` with a field appended
+#[derive(Transmittable, Debug, Clone, PartialEq, Default)]
+#[transmittable(delimited, schema)]
+struct ProfileV2 {
+    id: u16,
+    name: String,
+    age: u8,
+}
+
+read_and_write!(Profile;
+    (b"\x00\x00\x00\x00\x00\x00\x00\x0C\x00\x07\x00\x00\x00\x00\x00\x00\x00\x02hi", Ok(Profile { id: 7, name: "hi".into() })),
+);
+
+#[test]
+fn delimited_structs() {
+    let profile = Profile { id: 7, name: "hi".into() };
+    let newer = ProfileV2 { id: 7, name: "hi".into(), age: 30 };
+
+    // older decoders skip the appended field, newer ones default it
+    let bytes = crate::to_vec(&newer).unwrap();
+    assert_eq!(crate::from_slice(&bytes), Ok((profile.clone(), bytes.len())));
+
+    let bytes = crate::to_vec(&profile).unwrap();
+    assert_eq!(crate::from_slice(&bytes), Ok((ProfileV2 { age: 0, ..newer }, bytes.len())));
+
+    // the length prefix still has to cover the payload
+    assert!(crate::from_slice::<Profile>(&bytes[..bytes.len() - 1]).unwrap_err().is_eof());
+
+    #[cfg(feature = "schema")]
+    {
+        crate::schema::assert_wire_compatible::<Profile, ProfileV2>();
+        crate::schema::assert_wire_compatible::<ProfileV2, Profile>();
+        assert_eq!(<Profile as crate::Schema>::schema().size(), None);
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use crate::serde::{Config, Deserializer, Serializer, TagWidth};
+    use crate::{Error, Transmittable};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Point {
+        x: u16,
+        y: u16,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    enum Shape {
+        Empty,
+        Circle(u16),
+        Rect { w: u8, h: u8 },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Message {
+        text: String,
+        tags: Vec<String>,
+        reply_to: Option<u64>,
+        pair: (u8, bool),
+    }
+
+    #[derive(Transmittable, Debug, Clone, PartialEq)]
+    #[transmittable(schema)]
+    struct Envelope {
+        id: u8,
+        #[transmittable(serde)]
+        message: Message,
+    }
+
+    fn to_vec<T: Serialize>(value: &T, config: Config) -> Vec<u8> {
+        let mut serializer = Serializer::with_config(Vec::new(), config);
+        value.serialize(&mut serializer).unwrap();
+        serializer.into_inner()
+    }
+
+    fn from_slice<T: for<'de> Deserialize<'de>>(bytes: &[u8], config: Config) -> crate::Result<T> {
+        T::deserialize(&mut Deserializer::with_config(bytes, config))
+    }
+
+    #[test]
+    fn same_encoding() {
+        let config = Config::new(TagWidth::U8);
+
+        let point = Point { x: 1, y: 2 };
+        let bytes = crate::to_vec(&super::Point { x: 1, y: 2 }).unwrap();
+        assert_eq!(to_vec(&point, config), bytes);
+        assert_eq!(from_slice(&bytes, config), Ok(point));
+
+        for (shape, derived) in [
+            (Shape::Empty, super::Shape::Empty),
+            (Shape::Circle(256), super::Shape::Circle(256)),
+            (Shape::Rect { w: 3, h: 4 }, super::Shape::Rect { w: 3, h: 4 }),
+        ] {
+            let bytes = crate::to_vec(&derived).unwrap();
+            assert_eq!(to_vec(&shape, config), bytes);
+            assert_eq!(from_slice(&bytes, config), Ok(shape));
+        }
+
+        // tags default to a `u32`
+        assert_eq!(to_vec(&Shape::Circle(1), Config::default()), b"\x00\x00\x00\x01\x00\x01");
+        assert_eq!(from_slice::<Shape>(b"\x03", config), Err(Error::Serde("invalid value: integer `3`, expected variant index 0 <= i < 3".into())));
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    enum Direction {
+        Up,
+        Down,
+        Left,
+    }
+
+    #[test]
+    fn explicit_discriminants() {
+        // serde writes the index of the variant where the derive writes its discriminant
+        let config = Config::new(TagWidth::U16);
+        assert_eq!(to_vec(&Direction::Up, config), crate::to_vec(&super::Direction::Up).unwrap());
+        assert_eq!(to_vec(&Direction::Down, config), b"\x00\x01");
+        assert_eq!(crate::to_vec(&super::Direction::Down).unwrap(), b"\x00\x05");
+    }
+
+    #[test]
+    fn round_trip() {
+        let message = Message {
+            text: "hi".into(),
+            tags: vec!["a".into(), "bc".into()],
+            reply_to: Some(7),
+            pair: (1, true),
+        };
+
+        let mut bytes = Vec::new();
+        crate::serde::serialize(&message, &mut bytes).unwrap();
+        assert_eq!(bytes.len(), crate::serde::encoded_len(&message));
+        assert_eq!(crate::serde::deserialize::<Message, _>(&mut &bytes[..]), Ok(message.clone()));
+
+        // the text is encoded like a `String`, and the tuple is prefixed with its length
+        assert_eq!(&bytes[..10], crate::to_vec(&"hi".to_string()).unwrap());
+        assert_eq!(&bytes[bytes.len() - 10..], b"\x00\x00\x00\x00\x00\x00\x00\x02\x01\x01");
+
+        // truncated input fails like the `Transmittable` implementations
+        assert!(crate::serde::deserialize::<Message, _>(&mut &bytes[..bytes.len() - 1]).unwrap_err().is_eof());
+
+        let envelope = Envelope { id: 3, message };
+        let bytes = crate::to_vec(&envelope).unwrap();
+        assert_eq!(bytes.len(), envelope.encoded_len());
+        assert_eq!(crate::from_slice_exact(&bytes), Ok(envelope));
+        assert_eq!(Envelope::FIXED_SIZE, None);
+
+        #[cfg(feature = "schema")]
+        {
+            let crate::schema::Layout::Struct(layout) = <Envelope as crate::Schema>::schema() else { unreachable!() };
+            assert_eq!(layout.fields[1].layout, crate::schema::Layout::Opaque(std::any::type_name::<Message>().into()));
+        }
+    }
+}
+
+#[cfg(feature = "bytes")]
+read_and_write!(bytes::Bytes; (b"\x00\x00\x00\x00\x00\x00\x00\x02hi", Ok(bytes::Bytes::from_static(b"hi"))));
+
+#[cfg(feature = "bytes")]
+#[test]
+fn bytes_buffers() {
+    use bytes::{Buf, Bytes, BytesMut};
+
+    let point = Point { x: 1, y: 2 };
+    let mut buf = BytesMut::new();
+    crate::bytes::to_buf(&point, &mut buf).unwrap();
+    crate::bytes::to_buf(&Bytes::from_static(b"hi"), &mut buf).unwrap();
+    assert_eq!(&buf[..4], b"\x00\x01\x00\x02");
+
+    // a full `BufMut` rejects values which don't fit
+    let mut small = [0u8; 3];
+    assert!(crate::bytes::to_buf(&point, &mut small[..]).is_err());
+
+    let mut src = buf.freeze();
+    assert_eq!(crate::bytes::from_buf::<Point, _>(&mut src), Ok(point));
+    // the source is advanced past the decoded value
+    assert_eq!(src.remaining(), 10);
+
+    // decoding from a `Bytes` source shares its allocation
+    let start = src.as_ptr();
+    let hi = crate::bytes::split_bytes(&mut src).unwrap();
+    assert_eq!(hi, b"hi"[..]);
+    assert_eq!(hi.as_ptr(), start.wrapping_add(size_of::<usize>()));
+    assert!(!src.has_remaining());
+
+    let mut truncated = Bytes::from_static(b"\x00\x00\x00\x00\x00\x00\x00\x02h");
+    assert!(crate::bytes::split_bytes(&mut truncated).unwrap_err().is_eof());
+    assert_eq!(truncated.len(), 9);
+    assert!(crate::bytes::from_buf::<BytesMut, _>(&mut truncated).unwrap_err().is_eof());
+}
+
+#[cfg(all(feature = "bytes", feature = "tokio"))]
+#[tokio::test]
+async fn async_bytes_buffers() {
+    use crate::AsyncTransmittable;
+    use bytes::{Bytes, BytesMut};
+
+    let bytes = b"\x00\x00\x00\x00\x00\x00\x00\x02hi";
+    assert_eq!(Bytes::deserialize_async(&mut &bytes[..]).await, Ok(Bytes::from_static(b"hi")));
+    assert_eq!(BytesMut::deserialize_async(&mut &bytes[..]).await, Ok(BytesMut::from(&b"hi"[..])));
+    assert!(Bytes::deserialize_async(&mut &bytes[..9]).await.unwrap_err().is_eof());
+}
+
+#[cfg(feature = "uuid")]
+read_and_write!(uuid::Uuid; (b"\x67\xe5\x50\x44\x10\xb1\x42\x6f\x92\x47\xbb\x68\x0e\x5f\xe0\xc8", Ok(uuid::uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"))));
+
+#[cfg(feature = "smallvec")]
+read_and_write!(smallvec::SmallVec<[u8; 2]>;
+    (b"\x00\x00\x00\x00\x00\x00\x00\x01\x07",         Ok(smallvec::smallvec![7])),
+    (b"\x00\x00\x00\x00\x00\x00\x00\x03\x07\x08\x09", Ok(smallvec::smallvec![7, 8, 9])),
+);
+
+#[cfg(feature = "arrayvec")]
+read_and_write!(arrayvec::ArrayVec<u16, 2>;
+    (b"\x00\x00\x00\x00\x00\x00\x00\x02\x00\x07\x00\x08", Ok(arrayvec::ArrayVec::from([7, 8]))),
+    // the items of a sequence which doesn't fit aren't read
+    (b"\x00\x00\x00\x00\x00\x00\x00\x03", Err(Error::CapacityExceeded { len: 3, capacity: 2 })),
+);
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn index_maps() {
+    let map: indexmap::IndexMap<String, u8> = [("b".to_string(), 2), ("a".to_string(), 1)].into_iter().collect();
+
+    let bytes = crate::to_vec(&map).unwrap();
+    assert_eq!(bytes, b"\x00\x00\x00\x00\x00\x00\x00\x02\x00\x00\x00\x00\x00\x00\x00\x01b\x02\x00\x00\x00\x00\x00\x00\x00\x01a\x01");
+    assert_eq!(bytes.len(), map.encoded_len());
+
+    // the insertion order survives the round trip
+    let decoded: indexmap::IndexMap<String, u8> = crate::from_slice_exact(&bytes).unwrap();
+    assert!(decoded.keys().eq(["b", "a"]));
+}
+
+#[cfg(feature = "bitflags")]
+bitflags::bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Permissions: u16 {
+        const READ = 1;
+        const WRITE = 1 << 1;
+    }
+}
+
+#[cfg(feature = "bitflags")]
+crate::impl_bitflags!(Permissions);
+
+#[cfg(feature = "bitflags")]
+read_and_write!(Permissions;
+    (b"\x00\x03", Ok(Permissions::READ | Permissions::WRITE)),
+    // unknown bits are kept
+    (b"\x01\x01", Ok(Permissions::READ | Permissions::from_bits_retain(0x100))),
+);
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn duplicate_index_map_keys() {
+    let bytes = b"\x00\x00\x00\x00\x00\x00\x00\x02\x00\x00\x00\x00\x00\x00\x00\x01a\x01\x00\x00\x00\x00\x00\x00\x00\x01a\x02";
+    assert_eq!(crate::from_slice::<indexmap::IndexMap<String, u8>>(bytes), Err(Error::DuplicateKey(1)));
+}
+
+#[cfg(all(feature = "bitflags", feature = "schema"))]
+#[test]
+fn bitflags_schema() {
+    use crate::schema::{Integer, Layout, Schema};
+
+    assert_eq!(Permissions::schema(), Layout::Integer(Integer::new(false, 16)));
+}
+
+// the optional integrations decode asynchronously just like synchronously
+#[cfg(all(feature = "tokio", feature = "uuid", feature = "smallvec", feature = "arrayvec", feature = "indexmap", feature = "bitflags"))]
+#[tokio::test]
+async fn async_integrations() {
+    use crate::AsyncTransmittable;
+
+    macro_rules! assert_async {
+        ($ty:ty, $value:expr) => {
+            let value: $ty = $value;
+            let bytes = crate::to_vec(&value).unwrap();
+            assert_eq!(<$ty>::deserialize_async(&mut bytes.as_slice()).await, Ok(value));
+            assert!(<$ty>::deserialize_async(&mut &bytes[..bytes.len() - 1]).await.unwrap_err().is_eof());
+        };
+    }
+
+    assert_async!(uuid::Uuid, uuid::uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"));
+    assert_async!(smallvec::SmallVec<[u8; 2]>, smallvec::smallvec![7, 8, 9]);
+    assert_async!(arrayvec::ArrayVec<u16, 2>, arrayvec::ArrayVec::from([7, 8]));
+    assert_async!(indexmap::IndexMap<String, u8>, [("b".to_string(), 2), ("a".to_string(), 1)].into_iter().collect());
+    assert_async!(Permissions, Permissions::READ | Permissions::from_bits_retain(0x100));
+
+    assert_eq!(
+        arrayvec::ArrayVec::<u16, 2>::deserialize_async(&mut &b"\x00\x00\x00\x00\x00\x00\x00\x03"[..]).await,
+        Err(Error::CapacityExceeded { len: 3, capacity: 2 }),
+    );
+    assert_eq!(
+        indexmap::IndexMap::<u8, u8>::deserialize_async(&mut &b"\x00\x00\x00\x00\x00\x00\x00\x02\x01\x01\x01\x02"[..]).await,
+        Err(Error::DuplicateKey(1)),
+    );
+}
+
+read_and_write!(crate::Prefixed<u8, String>;
+    (b"\x02hi", Ok(crate::Prefixed::new("hi".to_string()))),
+    (b"\x02\xFF\xFE", Err(Error::Utf8DecodeError(String::from_utf8(vec![0xFF, 0xFE]).unwrap_err()))),
+);
+
+type ShortVec = crate::Prefixed<u16, Vec<u16>>;
+read_and_write!(ShortVec; (b"\x00\x02\x00\x07\x00\x08", Ok(crate::Prefixed::new(vec![7, 8]))));
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(async, schema)]
+struct Record {
+    #[transmittable(len = u8)]
+    name: String,
+    #[transmittable(len = u32)]
+    scores: Vec<u16>,
+    tag: u8,
+}
+
+read_and_write!(Record; (b"\x02ab\x00\x00\x00\x02\x00\x01\x00\x02\x03", Ok(Record { name: "ab".into(), scores: vec![1, 2], tag: 3 })));
+
+#[test]
+fn length_prefixes() {
+    let record = Record { name: "a".repeat(256), scores: Vec::new(), tag: 0 };
+    assert_eq!(crate::to_vec(&record), Err(Error::LengthOverflow { len: 256, max: 255 }));
+    assert_eq!(Record::FIXED_SIZE, None);
+    assert_eq!(<crate::Prefixed<u8, String>>::new("hi".into()).encoded_len(), 3);
+
+    #[cfg(feature = "schema")]
+    {
+        use crate::schema::{Integer, Layout, Length};
+
+        let Layout::Struct(layout) = <Record as crate::Schema>::schema() else { unreachable!() };
+        assert_eq!(layout.fields[0].layout, Layout::String { length: Length::Prefixed(Integer::new(false, 8)) });
+        assert_eq!(layout.fields[1].layout.size(), None);
+    }
+}
+
+type Name = crate::NulTerminated<String, 4>;
+read_and_write!(Name;
+    (b"abc\x00",   Ok(crate::NulTerminated("abc".to_string()))),
+    (b"abcde",     Err(Error::Unterminated { max: 4 })),
+);
+
+read_and_write!(std::ffi::CString; (b"hi\x00", Ok(std::ffi::CString::new("hi").unwrap())));
+
+type Padded = crate::FixedStr<4, b' '>;
+read_and_write!(Padded;
+    (b"ab  ", Ok(crate::FixedStr("ab".to_string()))),
+    (b"abcd", Ok(crate::FixedStr("abcd".to_string()))),
+);
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(async, schema)]
+struct Login {
+    #[transmittable(fixed = 8)]
+    user: String,
+    #[transmittable(nul_terminated)]
+    password: String,
+    #[transmittable(fixed = 3, pad = b'_')]
+    region: String,
+}
+
+read_and_write!(Login; (b"root\x00\x00\x00\x00hunter2\x00eu_", Ok(Login { user: "root".into(), password: "hunter2".into(), region: "eu".into() })));
+
+#[test]
+fn c_strings() {
+    let login = Login { user: "administrator".into(), password: "".into(), region: "".into() };
+    assert_eq!(crate::to_vec(&login), Err(Error::StringTooLong { len: 13, max: 8 }));
+
+    let login = Login { user: "root".into(), password: "a\0b".into(), region: "".into() };
+    assert_eq!(crate::to_vec(&login), Err(Error::InteriorNul(1)));
+    assert_eq!(login.encoded_len(), 8 + 4 + 3);
+
+    assert_eq!(crate::to_vec(&Name::default()).unwrap(), b"\x00");
+    assert_eq!(crate::to_vec(&crate::NulTerminated::<String, 2>("abc".into())), Err(Error::StringTooLong { len: 3, max: 2 }));
+    assert!(crate::from_slice::<Name>(b"ab").unwrap_err().is_eof());
+    assert_eq!(<crate::FixedStr<32>>::FIXED_SIZE, Some(32));
+
+    #[cfg(feature = "schema")]
+    {
+        use crate::schema::{Layout, Length, Schema};
+
+        let Layout::Struct(layout) = <Login as crate::Schema>::schema() else { unreachable!() };
+        assert_eq!(layout.fields[0].layout, Layout::String { length: Length::Padded { size: 8, padding: 0 } });
+        assert_eq!(layout.fields[1].layout, Layout::String { length: Length::Terminated(0) });
+        assert_eq!(layout.fields[2].layout.size(), Some(3));
+
+        let changes = crate::schema::breaking_changes(&<crate::FixedStr<8>>::schema(), &Name::schema());
+        assert_eq!(changes[0].to_string(), "value: the length changed from 8 bytes padded with 0x00 to a 0x00 terminator");
+    }
+}
+
+type CharArray = crate::FixedStr<4>;
+read_and_write!(CharArray;
+    (b"ab\x00\x00", Ok(crate::FixedStr("ab".to_string()))),
+    (b"abcd",         Ok(crate::FixedStr("abcd".to_string()))),
+);
+
+#[test]
+fn nul_padded_strings() {
+    // like a C string, the first NUL ends the string even if garbage follows it
+    assert_eq!(crate::from_slice_exact::<CharArray>(b"a\x00bc"), Ok(crate::FixedStr("a".to_string())));
+    assert_eq!(crate::from_slice_exact::<CharArray>(b"\x00\xFFbc"), Ok(crate::FixedStr(String::new())));
+    assert_eq!(crate::to_vec(&crate::FixedStr::<4>("a\0b".to_string())), Err(Error::InteriorNul(1)));
+
+    // other padding bytes are only stripped from the end
+    assert_eq!(crate::from_slice_exact::<Padded>(b"a b "), Ok(crate::FixedStr("a b".to_string())));
+    assert_eq!(crate::to_vec(&crate::FixedStr::<4, b' '>("a\0".to_string())).unwrap(), b"a\x00  ");
+}
+
+read_and_write!(crate::Rest; (b"\x01\x02\x03", Ok(crate::Remaining(vec![1, 2, 3]))), (b"", Ok(crate::Remaining(Vec::new()))));
+
+#[test]
+fn remaining_interrupted() {
+    // a reader interrupted before every chunk, which `std` readers are expected to retry
+    struct Interrupting<'a> {
+        bytes: &'a [u8],
+        interrupted: bool,
+    }
+
+    impl std::io::Read for Interrupting<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.interrupted = !self.interrupted;
+            if self.interrupted {
+                return Err(std::io::ErrorKind::Interrupted.into());
+            }
+
+            let len = buf.len().min(1);
+            std::io::Read::read(&mut self.bytes, &mut buf[..len])
+        }
+    }
+
+    let mut reader = Interrupting { bytes: b"\x01\x02\x03", interrupted: false };
+    assert_eq!(crate::Rest::deserialize(&mut reader), Ok(crate::Remaining(vec![1, 2, 3])));
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(schema)]
+struct Chunk {
+    id: u8,
+    #[transmittable(rest)]
+    payload: Vec<u8>,
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(async)]
+#[repr(u8)]
+enum Command {
+    Join(u8),
+    Say(#[transmittable(rest)] String),
+}
+
+read_and_write!(Chunk; (b"\x07abc", Ok(Chunk { id: 7, payload: b"abc".to_vec() })));
+read_and_write!(Command; (b"\x00\x03", Ok(Command::Join(3))), (b"\x01hello", Ok(Command::Say("hello".into()))));
+
+#[test]
+fn remaining_fields() {
+    let chunk = Chunk { id: 7, payload: b"abc".to_vec() };
+    assert_eq!(chunk.encoded_len(), 4);
+
+    // frames end the payload, so several of them can follow each other
+    let config = crate::FrameConfig::default();
+    let mut buf = Vec::new();
+    crate::write_framed(&mut buf, &chunk, &config).unwrap();
+    crate::write_framed(&mut buf, &Chunk { id: 8, payload: Vec::new() }, &config).unwrap();
+
+    let mut reader = buf.as_slice();
+    assert_eq!(crate::read_framed(&mut reader, &config), Ok(chunk));
+    assert_eq!(crate::read_framed(&mut reader, &config), Ok(Chunk { id: 8, payload: Vec::new() }));
+    assert!(reader.is_empty());
+
+    #[cfg(feature = "schema")]
+    {
+        use crate::schema::{Layout, Length, Schema};
+
+        let Layout::Struct(layout) = Chunk::schema() else { unreachable!() };
+        assert_eq!(layout.fields[1].layout, Layout::Sequence { length: Length::Remaining, element: Box::new(Layout::Integer(crate::schema::Integer::new(false, 8))) });
+    }
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(async, schema)]
+struct Inventory {
+    n_items: u16,
+    owner: u8,
+    #[transmittable(count = "n_items")]
+    items: Vec<Point>,
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(async)]
+#[repr(u8)]
+enum Blob {
+    Text {
+        len: u8,
+        #[transmittable(size_bytes = "len")]
+        text: String,
+    },
+    Points {
+        size: u32,
+        #[transmittable(size_bytes = "size")]
+        points: Vec<Point>,
+    },
+}
+
+read_and_write!(Inventory;
+    (b"\x00\x02\x09\x00\x01\x00\x02\x00\x03\x00\x04", Ok(Inventory { n_items: 2, owner: 9, items: vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }] })),
+    (b"\x00\x00\x09", Ok(Inventory { n_items: 0, owner: 9, items: Vec::new() })),
+);
+
+read_and_write!(Blob;
+    (b"\x00\x02hi", Ok(Blob::Text { len: 2, text: "hi".into() })),
+    (b"\x01\x00\x00\x00\x04\x00\x01\x00\x02", Ok(Blob::Points { size: 4, points: vec![Point { x: 1, y: 2 }] })),
+);
+
+#[test]
+fn length_fields() {
+    let inventory = Inventory { n_items: 3, owner: 0, items: vec![Point { x: 1, y: 2 }] };
+    assert_eq!(crate::to_vec(&inventory), Err(Error::LengthMismatch { expected: 3, actual: 1 }));
+    assert_eq!(inventory.encoded_len(), 7);
+
+    let blob = Blob::Text { len: 1, text: "hi".into() };
+    assert_eq!(crate::to_vec(&blob), Err(Error::LengthMismatch { expected: 1, actual: 2 }));
+
+    // the size has to cover whole items
+    assert!(crate::from_slice::<Blob>(b"\x01\x00\x00\x00\x03\x00\x01\x00").unwrap_err().is_eof());
+
+    #[cfg(feature = "schema")]
+    {
+        use crate::schema::{Layout, Length, Schema};
+
+        let Layout::Struct(layout) = Inventory::schema() else { unreachable!() };
+        assert_eq!(layout.fields[2].layout, <Vec<Point>>::schema().with_length(Length::CountField("n_items".into())));
+        assert_eq!(layout.fields[2].layout.size(), None);
+        assert_eq!(Layout::String { length: Length::Remaining }.with_length(Length::SizeField("len".into())), Layout::String { length: Length::SizeField("len".into()) });
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn async_length_fields() {
+    use crate::AsyncTransmittable;
+
+    let inventory = Inventory { n_items: 2, owner: 9, items: vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }] };
+    let bytes = crate::to_vec(&inventory).unwrap();
+    assert_eq!(Inventory::deserialize_async(&mut bytes.as_slice()).await, Ok(inventory));
+
+    for blob in [Blob::Text { len: 2, text: "hi".into() }, Blob::Points { size: 4, points: vec![Point { x: 1, y: 2 }] }] {
+        let bytes = crate::to_vec(&blob).unwrap();
+        assert_eq!(Blob::deserialize_async(&mut bytes.as_slice()).await, Ok(blob));
+    }
+
+    assert!(Blob::deserialize_async(&mut &b"\x01\x00\x00\x00\x03\x00\x01\x00"[..]).await.unwrap_err().is_eof());
+    assert!(Inventory::deserialize_async(&mut &b"\x00\x02\x09\x00\x01\x00\x02"[..]).await.unwrap_err().is_eof());
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[transmittable(schema)]
+struct Flagged {
+    flags: u8,
+    #[transmittable(if = "self.flags & 0x1 != 0")]
+    id: Option<u32>,
+    #[transmittable(if = "self.flags & 0x2 != 0", len = u8)]
+    name: Option<String>,
+}
+
+#[derive(Transmittable, Debug, Clone, PartialEq)]
+#[repr(u8)]
+enum Reply {
+    Ok,
+    Failed {
+        code: u16,
+        #[transmittable(if = "self.code >= 500", nul_terminated)]
+        reason: Option<String>,
+    },
+}
+
+read_and_write!(Flagged;
+    (b"\x00", Ok(Flagged { flags: 0, id: None, name: None })),
+    (b"\x01\x00\x00\x00\x07", Ok(Flagged { flags: 1, id: Some(7), name: None })),
+    (b"\x03\x00\x00\x00\x07\x02hi", Ok(Flagged { flags: 3, id: Some(7), name: Some("hi".into()) })),
+    (b"\x02\x02hi", Ok(Flagged { flags: 2, id: None, name: Some("hi".into()) })),
+);
+
+read_and_write!(Reply;
+    (b"\x00", Ok(Reply::Ok)),
+    (b"\x01\x01\x90", Ok(Reply::Failed { code: 400, reason: None })),
+    (b"\x01\x01\xf4down\x00", Ok(Reply::Failed { code: 500, reason: Some("down".into()) })),
+);
+
+#[test]
+fn conditional_fields() {
+    assert_eq!(<Flagged as Transmittable>::FIXED_SIZE, None);
+
+    let missing = Flagged { flags: 1, id: None, name: None };
+    assert_eq!(crate::to_vec(&missing), Err(Error::ConditionMismatch { present: false }));
+
+    let unexpected = Reply::Failed { code: 404, reason: Some("gone".into()) };
+    assert_eq!(crate::to_vec(&unexpected), Err(Error::ConditionMismatch { present: true }));
+
+    #[cfg(feature = "schema")]
+    {
+        use crate::schema::{Layout, Schema};
+
+        let Layout::Struct(layout) = Flagged::schema() else { unreachable!() };
+        assert_eq!(layout.fields[1].layout, Layout::Conditional { condition: "self.flags & 0x1 != 0".into(), layout: Box::new(u32::schema()) });
+        assert_eq!(layout.fields[2].layout, <crate::Prefixed<u8, String>>::schema().conditional("self.flags & 0x2 != 0"));
+        assert!(Flagged::schema().markdown().to_string().contains("| 1 | id | u32 if `self.flags & 0x1 != 0` |"));
+    }
+}
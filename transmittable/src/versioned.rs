@@ -0,0 +1,59 @@
+//! Types whose encoding depends on the protocol version spoken by the peer.
+//!
+//! Deriving [`Transmittable`] on a struct annotated with `#[transmittable(version = N)]` implements [`Versioned`]
+//! as well. Fields marked with `#[transmittable(since = V)]` are only written for peers speaking version `V` or
+//! newer, and are filled with their [`Default`] value when decoding data of an older version:
+//!
+//! ```ignore
+//! #[derive(Transmittable)]
+//! #[transmittable(version = 3)]
+//! struct Status {
+//!     online: bool,
+//!     #[transmittable(since = 2)]
+//!     message: String,
+//!     #[transmittable(since = 3)]
+//!     away_since: Option<u64>,
+//! }
+//!
+//! // a peer speaking version 1 only receives `online`
+//! status.serialize_versioned(1, &mut writer)?;
+//! ```
+//!
+//! The plain [`Transmittable`] methods always use the newest version.
+//!
+//! Fields are encoded with their plain [`Transmittable`] impl, so a field whose type is versioned itself is always
+//! written in its newest version. Marking it with `#[transmittable(versioned)]` encodes it with the version the outer
+//! struct is encoded with instead, capped at the newest version of the field's type:
+//!
+//! ```ignore
+//! #[derive(Transmittable)]
+//! #[transmittable(version = 4)]
+//! struct Presence {
+//!     #[transmittable(versioned)]
+//!     status: Status,
+//!     #[transmittable(since = 4)]
+//!     room: u8,
+//! }
+//! ```
+//!
+//! Such fields have no fixed size and can't be decoded asynchronously.
+
+use crate::{Error, Reader, Result, Transmittable, Writer};
+
+pub trait Versioned: Transmittable + Sized {
+    /// The newest version of the encoding.
+    const VERSION: u32;
+
+    fn serialize_versioned<W: Writer>(&self, version: u32, writer: &mut W) -> Result<()>;
+    fn deserialize_versioned<R: Reader>(version: u32, reader: &mut R) -> Result<Self>;
+    fn encoded_len_versioned(&self, version: u32) -> usize;
+}
+
+/// Returns an error if `version` is newer than the newest version of `T`.
+pub fn check_version<T: Versioned>(version: u32) -> Result<()> {
+    if version > T::VERSION {
+        return Err(Error::UnsupportedVersion { version, max: T::VERSION });
+    }
+
+    Ok(())
+}